@@ -0,0 +1,115 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::core::model::Model;
+
+pub(crate) const BOOT_ROM_SIZE: usize = 0x100;
+// The CGB boot ROM goes on at 0x0200-0x08FF, leaving the cartridge header visible in between
+pub(crate) const CGB_BOOT_ROM_SIZE: usize = 0x900;
+// Writing 1 to bit 0 unmaps the boot ROM, until the next reset
+pub(crate) const BOOT_ROM_DISABLE_ADDRESS: u16 = 0xFF50;
+
+#[derive(Debug)]
+pub(crate) enum BootRomError {
+    Io(io::Error),
+    InvalidSize { size: usize, expected: usize }
+}
+
+impl Display for BootRomError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BootRomError::Io(error) => write!(f, "could not read the boot ROM: {}", error),
+            BootRomError::InvalidSize { size, expected } =>
+                write!(f, "the boot ROM is {} bytes long, instead of {}", size, expected)
+        }
+    }
+}
+
+impl Error for BootRomError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BootRomError::Io(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for BootRomError {
+    fn from(error: io::Error) -> Self {
+        BootRomError::Io(error)
+    }
+}
+
+// The program mapped over 0x0000-0x00FF at power on, which scrolls the logo and checks the header.
+// Each model has its own, the CGB one being larger.
+#[derive(Debug)]
+pub struct BootRom {
+    data: Vec<u8>
+}
+
+impl BootRom {
+    pub(crate) fn from_file<P: AsRef<Path>>(path: P, model: Model) -> Result<Self, BootRomError> {
+        BootRom::new(fs::read(path)?, model)
+    }
+
+    pub(crate) fn new(data: Vec<u8>, model: Model) -> Result<Self, BootRomError> {
+        let expected = model.boot_rom_size();
+        if data.len() != expected {
+            return Err(BootRomError::InvalidSize { size: data.len(), expected });
+        }
+        Ok(BootRom { data })
+    }
+
+    // Whether the address is mapped to the boot ROM rather than to the cartridge
+    pub(crate) fn maps(&self, address: u16) -> bool {
+        let address = address as usize;
+        address < BOOT_ROM_SIZE || (0x0200..self.data.len()).contains(&address)
+    }
+
+    pub(crate) fn read(&self, address: u16) -> u8 {
+        self.data[address as usize]
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::boot::{BootRom, BootRomError};
+    use crate::core::model::Model;
+
+    #[test]
+    fn test_new(){
+        let mut data = vec![0x00; 0x100];
+        data[0xFF] = 0x50;
+
+        let boot_rom = BootRom::new(data, Model::Dmg).unwrap();
+
+        assert_eq!(0x50, boot_rom.read(0xFF));
+        assert!(boot_rom.maps(0x00FF));
+        assert!(!boot_rom.maps(0x0100));
+        assert!(!boot_rom.maps(0x0200));
+    }
+
+    #[test]
+    fn test_new_cgb(){
+        let boot_rom = BootRom::new(vec![0x00; 0x900], Model::Cgb).unwrap();
+
+        assert!(boot_rom.maps(0x0000));
+        assert!(!boot_rom.maps(0x0100));
+        assert!(!boot_rom.maps(0x01FF));
+        assert!(boot_rom.maps(0x0200));
+        assert!(boot_rom.maps(0x08FF));
+        assert!(!boot_rom.maps(0x0900));
+    }
+
+    #[test]
+    fn test_new_invalid_size(){
+        let result = BootRom::new(vec![0x00; 0x900], Model::Dmg);
+
+        assert!(matches!(result, Err(BootRomError::InvalidSize { size: 0x900, expected: 0x100 })));
+        assert_eq!("the boot ROM is 2304 bytes long, instead of 256", result.unwrap_err().to_string());
+        assert!(BootRom::new(vec![0x00; 0x100], Model::Agb).is_err());
+    }
+}
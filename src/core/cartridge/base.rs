@@ -0,0 +1,603 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::core::cartridge::camera::PocketCamera;
+use crate::core::cartridge::header::{global_checksum, Header, Mapper};
+use crate::core::cartridge::huc1::HuC1;
+use crate::core::cartridge::huc3::HuC3;
+use crate::core::cartridge::mbc::{Mbc, NoMbc};
+use crate::core::cartridge::mbc1::{is_multicart, Mbc1};
+use crate::core::cartridge::mbc2::{Mbc2, MBC2_RAM_SIZE};
+use crate::core::cartridge::mbc3::Mbc3;
+use crate::core::cartridge::mbc5::Mbc5;
+use crate::core::cartridge::mbc7::{Mbc7, MBC7_EEPROM_SIZE};
+use crate::core::cartridge::mmm01::Mmm01;
+use crate::core::cartridge::multicart::{detect_unlicensed_mapper, M161, mmm01_menu_offset, WisdomTree};
+use crate::core::cartridge::peripheral::Peripheral;
+
+#[derive(Debug)]
+pub(crate) enum CartridgeError {
+    Io(io::Error),
+    // Not even large enough to hold the header
+    TooSmall { size: usize },
+    UnknownCartridgeType(u8),
+    UnknownRomSize(u8),
+    UnknownRamSize(u8),
+    // A valid cartridge type, whose memory bank controller is not emulated
+    UnsupportedMapper(Mapper),
+    // A peripheral was connected to a cartridge without the hardware it drives
+    MissingHardware(&'static str),
+    HeaderChecksum { expected: u8, computed: u8 }
+}
+
+impl Display for CartridgeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CartridgeError::Io(error) => write!(f, "could not read the ROM: {}", error),
+            CartridgeError::TooSmall { size } =>
+                write!(f, "the ROM is {} bytes long, too small to hold a header", size),
+            CartridgeError::UnknownCartridgeType(code) => write!(f, "unknown cartridge type {:#04x}", code),
+            CartridgeError::UnknownRomSize(code) => write!(f, "unknown ROM size code {:#04x}", code),
+            CartridgeError::UnknownRamSize(code) => write!(f, "unknown RAM size code {:#04x}", code),
+            CartridgeError::UnsupportedMapper(mapper) => write!(f, "unsupported memory bank controller {:?}", mapper),
+            CartridgeError::MissingHardware(name) => write!(f, "the cartridge has no {}", name),
+            CartridgeError::HeaderChecksum { expected, computed } =>
+                write!(f, "header checksum mismatch: expected {:#04x}, computed {:#04x}", expected, computed)
+        }
+    }
+}
+
+impl Error for CartridgeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CartridgeError::Io(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for CartridgeError {
+    fn from(error: io::Error) -> Self {
+        CartridgeError::Io(error)
+    }
+}
+
+#[derive(Debug)]
+pub struct Cartridge {
+    pub(crate) header: Header,
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    mbc: Box<dyn Mbc>,
+    battery: bool,
+    // Only informative, as the hardware never checks it and patched ROMs or homebrew often get it wrong
+    global_checksum_matches: bool,
+    // Set when loaded from a file, for the save to go next to it
+    rom_path: Option<PathBuf>,
+    // Where the battery save goes, with a .sav extension. Only set once load_save has run, so that
+    // an unread save never gets overwritten.
+    save_path: Option<PathBuf>,
+    // The RAM was written since the save last was
+    ram_written: bool,
+    // The game disabled the RAM after writing to it, so the save is worth writing back
+    save_due: bool
+}
+
+impl Cartridge {
+    pub(crate) fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CartridgeError> {
+        let mut cartridge = Cartridge::new(fs::read(&path)?)?;
+        cartridge.rom_path = Some(path.as_ref().to_path_buf());
+        Ok(cartridge)
+    }
+
+    // Unlicensed multicarts are recognized by their contents, as their header can not be trusted
+    // to give their mapper, size or checksum. Images smaller than the header says are padded with
+    // open bus, larger ones are kept whole.
+    pub(crate) fn new(mut rom: Vec<u8>) -> Result<Self, CartridgeError> {
+        let menu_offset = mmm01_menu_offset(&rom);
+        let header = Header::parse(&rom[menu_offset.unwrap_or(0)..])?;
+        let unlicensed_mapper = detect_unlicensed_mapper(&rom, &header);
+        let mut global_checksum_matches = true;
+        if menu_offset.is_none() && unlicensed_mapper.is_none() {
+            global_checksum_matches = global_checksum(&rom) == header.global_checksum;
+            if rom.len() < header.rom_size {
+                rom.resize(header.rom_size, 0xFF);
+            }
+        }
+        let mut ram_size = if header.cartridge_type.ram { header.ram_size } else { 0 };
+        let mut battery = header.cartridge_type.battery;
+        let mbc: Box<dyn Mbc> = match unlicensed_mapper.unwrap_or(header.cartridge_type.mapper) {
+            Mapper::None => Box::new(NoMbc),
+            Mapper::Mbc1 => Box::new(Mbc1::new(is_multicart(&rom))),
+            // Without a menu at the end, the header at the start was the menu's
+            Mapper::Mmm01 => Box::new(Mmm01::new(menu_offset.is_none())),
+            Mapper::Mbc2 => {
+                ram_size = MBC2_RAM_SIZE;
+                Box::new(Mbc2::new())
+            }
+            Mapper::Mbc3 => Box::new(Mbc3::new(header.cartridge_type.timer)),
+            Mapper::Mbc5 => Box::new(Mbc5::new(header.cartridge_type.rumble)),
+            Mapper::Mbc7 => {
+                ram_size = MBC7_EEPROM_SIZE;
+                Box::new(Mbc7::new())
+            }
+            Mapper::PocketCamera => Box::new(PocketCamera::new()),
+            Mapper::HuC1 => Box::new(HuC1::new()),
+            Mapper::HuC3 => Box::new(HuC3::new()),
+            Mapper::M161 => {
+                // The header claims an MBC3 with RAM and battery, the board has neither
+                ram_size = 0;
+                battery = false;
+                Box::new(M161::new())
+            }
+            Mapper::WisdomTree => Box::new(WisdomTree::new()),
+            mapper => return Err(CartridgeError::UnsupportedMapper(mapper))
+        };
+        let ram = vec![0; ram_size];
+        Ok(Cartridge { header, rom, ram, mbc, battery, global_checksum_matches, rom_path: None, save_path: None, ram_written: false, save_due: false })
+    }
+
+    // 0x0000-0x7FFF
+    pub(crate) fn read_rom(&self, address: u16) -> u8 {
+        self.mbc.read_rom(&self.rom, address)
+    }
+
+    pub(crate) fn write_rom(&mut self, address: u16, value: u8) {
+        self.mbc.write_rom(address, value);
+        // Games disable the RAM once done saving, by writing anything but 0x0A to 0x0000-0x1FFF
+        if address < 0x2000 && value & 0x0F != 0x0A && self.ram_written {
+            self.save_due = true;
+        }
+    }
+
+    // 0xA000-0xBFFF
+    pub(crate) fn read_ram(&self, address: u16) -> u8 {
+        self.mbc.read_ram(&self.ram, address)
+    }
+
+    pub(crate) fn write_ram(&mut self, address: u16, value: u8) {
+        self.mbc.write_ram(&mut self.ram, address, value);
+        self.ram_written = true;
+    }
+
+    pub(crate) fn tick(&mut self, cycles: u64) {
+        self.mbc.tick(&mut self.ram, cycles);
+    }
+
+    pub(crate) fn connect(&mut self, peripheral: Peripheral) -> Result<(), CartridgeError> {
+        self.mbc.connect(peripheral).map_err(|peripheral| CartridgeError::MissingHardware(peripheral.name()))
+    }
+
+    pub(crate) fn global_checksum_matches(&self) -> bool {
+        self.global_checksum_matches
+    }
+
+    pub(crate) fn has_battery(&self) -> bool {
+        self.battery
+    }
+
+    // What the battery keeps while the console is off: the RAM, followed by any clock state
+    pub(crate) fn battery_save(&self) -> Vec<u8> {
+        let mut save = self.ram.clone();
+        save.extend(self.mbc.save_footer());
+        save
+    }
+
+    // A save shorter than the RAM only restores what it covers
+    pub(crate) fn load_battery_save(&mut self, save: &[u8]) {
+        let ram_size = self.ram.len().min(save.len());
+        self.ram[..ram_size].copy_from_slice(&save[..ram_size]);
+        self.mbc.load_footer(&save[ram_size..]);
+    }
+
+    // Restores the save found next to the ROM, if any, and lets flush_save write it back. Meant to
+    // be called once the time source is set, so that clocks catch up with the time spent off.
+    pub(crate) fn load_save(&mut self) -> Result<(), CartridgeError> {
+        let save_path = match &self.rom_path {
+            Some(rom_path) if self.has_battery() => rom_path.with_extension("sav"),
+            _ => return Ok(())
+        };
+        match fs::read(&save_path) {
+            Ok(save) => self.load_battery_save(&save),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            // Keeps the save that could not be read from being overwritten
+            Err(error) => return Err(error.into())
+        }
+        self.save_path = Some(save_path);
+        Ok(())
+    }
+
+    // Raw RAM, followed by the clock footer on MBC3 and HuC3, as BGB and VBA-M lay MBC3 saves out
+    pub(crate) fn flush_save(&self) -> Result<(), CartridgeError> {
+        if let Some(save_path) = &self.save_path {
+            fs::write(save_path, self.battery_save())?;
+        }
+        Ok(())
+    }
+
+    // Flushes the save once the game is done writing it. A failed write is not retried until the
+    // game saves again.
+    pub(crate) fn flush_due_save(&mut self) -> Result<(), CartridgeError> {
+        if !self.save_due {
+            return Ok(());
+        }
+        self.save_due = false;
+        self.flush_save()?;
+        self.ram_written = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use std::cell::Cell;
+    use std::{env, fs, process};
+    use std::error::Error;
+    use std::rc::Rc;
+    use crate::core::cartridge::base::{Cartridge, CartridgeError};
+    use crate::core::cartridge::header::{fix_checksums, Mapper, NINTENDO_LOGO, test_rom};
+    use crate::core::cartridge::huc3::HUC3_FOOTER_SIZE;
+    use crate::core::cartridge::infrared::LoopbackTransceiver;
+    use crate::core::cartridge::peripheral::Peripheral;
+    use crate::core::cartridge::rtc::{CYCLES_PER_SECOND, CycleTimeSource};
+    use crate::core::cartridge::sensor::SensorFrame;
+
+    #[test]
+    fn test_new(){
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        rom[0x7FFF] = 0x12;
+        fix_checksums(&mut rom);
+
+        let mut cartridge = Cartridge::new(rom).unwrap();
+        cartridge.write_rom(0x7FFF, 0x34);
+        cartridge.write_ram(0xA000, 0x34);
+
+        assert_eq!("TEST", cartridge.header.title);
+        assert_eq!(0x12, cartridge.read_rom(0x7FFF));
+        assert_eq!(0xFF, cartridge.read_ram(0xA000));
+    }
+
+    #[test]
+    fn test_ram(){
+        let mut cartridge = Cartridge::new(test_rom(0x08, 0x00, 0x02)).unwrap();
+
+        cartridge.write_ram(0xA000, 0x12);
+        cartridge.write_ram(0xBFFF, 0x34);
+
+        assert_eq!(0x12, cartridge.read_ram(0xA000));
+        assert_eq!(0x34, cartridge.read_ram(0xBFFF));
+    }
+
+    #[test]
+    fn test_mbc1(){
+        let mut rom = test_rom(0x03, 0x02, 0x03);
+        rom[0x3 * 0x4000] = 0x12;
+        fix_checksums(&mut rom);
+        let mut cartridge = Cartridge::new(rom).unwrap();
+
+        cartridge.write_rom(0x2000, 0x03);
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_rom(0x4000, 0x01);
+        cartridge.write_rom(0x6000, 0x01);
+        cartridge.write_ram(0xA000, 0x34);
+
+        assert_eq!(0x12, cartridge.read_rom(0x4000));
+        assert_eq!(0x34, cartridge.ram[0x2000]);
+    }
+
+    #[test]
+    fn test_mbc2(){
+        let mut cartridge = Cartridge::new(test_rom(0x06, 0x03, 0x00)).unwrap();
+
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_ram(0xA000, 0x34);
+
+        assert_eq!(0x200, cartridge.ram.len());
+        assert_eq!(0xF4, cartridge.read_ram(0xA200));
+    }
+
+    #[test]
+    fn test_battery_save_with_rtc(){
+        let mut cartridge = Cartridge::new(test_rom(0x10, 0x00, 0x02)).unwrap();
+        cartridge.connect(Peripheral::Clock(Box::new(CycleTimeSource::new(0)))).unwrap();
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_ram(0xA000, 0x12);
+        cartridge.write_rom(0x4000, 0x09);
+        cartridge.write_ram(0xA000, 0x05);
+        cartridge.tick(60 * CYCLES_PER_SECOND);
+
+        let save = cartridge.battery_save();
+        let mut loaded = Cartridge::new(test_rom(0x10, 0x00, 0x02)).unwrap();
+        loaded.load_battery_save(&save);
+        loaded.write_rom(0x0000, 0x0A);
+        loaded.write_rom(0x6000, 0x00);
+        loaded.write_rom(0x6000, 0x01);
+        loaded.write_rom(0x4000, 0x09);
+
+        assert!(cartridge.has_battery());
+        assert_eq!(0x2000 + 48, save.len());
+        assert_eq!(0x06, loaded.read_ram(0xA000));
+        loaded.write_rom(0x4000, 0x00);
+        assert_eq!(0x12, loaded.read_ram(0xA000));
+    }
+
+    #[test]
+    fn test_mbc5_rumble(){
+        let motor_on = Rc::new(Cell::new(false));
+        let callback_motor_on = motor_on.clone();
+        let mut cartridge = Cartridge::new(test_rom(0x1E, 0x01, 0x03)).unwrap();
+        cartridge.connect(Peripheral::Rumble(Box::new(move |on| callback_motor_on.set(on)))).unwrap();
+
+        cartridge.write_rom(0x4000, 0x08);
+
+        assert!(motor_on.get());
+    }
+
+    #[test]
+    fn test_mbc7(){
+        let mut cartridge = Cartridge::new(test_rom(0x22, 0x05, 0x00)).unwrap();
+        let mut save = vec![0x00; 0x100];
+        save[0x00] = 0x12;
+        cartridge.load_battery_save(&save);
+        cartridge.connect(Peripheral::Tilt { x: -1.0, y: 0.0 }).unwrap();
+
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_rom(0x4000, 0x40);
+        cartridge.write_ram(0xA000, 0x55);
+        cartridge.write_ram(0xA010, 0xAA);
+
+        assert!(cartridge.has_battery());
+        assert_eq!(0x60, cartridge.read_ram(0xA020));
+        assert_eq!(0x81, cartridge.read_ram(0xA030));
+        assert_eq!(save, cartridge.battery_save());
+    }
+
+    #[test]
+    fn test_hudson_mappers(){
+        let mut huc1 = Cartridge::new(test_rom(0xFF, 0x02, 0x03)).unwrap();
+        huc1.connect(Peripheral::Infrared(Box::new(LoopbackTransceiver::default()))).unwrap();
+        huc1.write_rom(0x0000, 0x0E);
+        huc1.write_ram(0xA000, 0x01);
+
+        assert_eq!(0xC1, huc1.read_ram(0xA000));
+
+        let huc3 = Cartridge::new(test_rom(0xFE, 0x02, 0x03)).unwrap();
+
+        assert!(huc3.has_battery());
+        assert_eq!(0x8000 + HUC3_FOOTER_SIZE, huc3.battery_save().len());
+    }
+
+    #[test]
+    fn test_connect_missing_hardware(){
+        let mut mbc1 = Cartridge::new(test_rom(0x03, 0x02, 0x03)).unwrap();
+        let mut mbc3 = Cartridge::new(test_rom(0x13, 0x02, 0x03)).unwrap();
+
+        let error = mbc1.connect(Peripheral::Clock(Box::new(CycleTimeSource::new(0)))).unwrap_err();
+
+        assert!(matches!(error, CartridgeError::MissingHardware("clock")));
+        assert_eq!("the cartridge has no clock", error.to_string());
+        assert!(mbc3.connect(Peripheral::Clock(Box::new(CycleTimeSource::new(0)))).is_err());
+        assert!(mbc1.connect(Peripheral::Rumble(Box::new(|_| {}))).is_err());
+        assert!(mbc1.connect(Peripheral::Tilt { x: 0.0, y: 0.0 }).is_err());
+        assert!(mbc1.connect(Peripheral::CameraFrame(SensorFrame::uniform(0x00))).is_err());
+    }
+
+    #[test]
+    fn test_pocket_camera(){
+        let mut cartridge = Cartridge::new(test_rom(0xFC, 0x05, 0x04)).unwrap();
+        cartridge.connect(Peripheral::CameraFrame(SensorFrame::uniform(0x00))).unwrap();
+        cartridge.write_rom(0x4000, 0x10);
+        cartridge.write_ram(0xA006, 0x01);
+        cartridge.write_ram(0xA000, 0x01);
+
+        cartridge.tick(2 * (32446 + 512));
+
+        assert_eq!(0x00, cartridge.read_ram(0xA000));
+        assert_eq!(0x20000, cartridge.battery_save().len());
+        assert_eq!(0b1000_1000, cartridge.battery_save()[0x0100]);
+    }
+
+    #[test]
+    fn test_mmm01_menu_header(){
+        let mut rom = test_rom(0x01, 0x03, 0x00);
+        let mut menu = test_rom(0x0D, 0x03, 0x03);
+        menu[0x0104..0x0134].copy_from_slice(&NINTENDO_LOGO);
+        menu[0x0150] = 0x12;
+        fix_checksums(&mut menu);
+        rom[0x38000..].copy_from_slice(&menu[..0x8000]);
+
+        let cartridge = Cartridge::new(rom).unwrap();
+
+        assert_eq!(Mapper::Mmm01, cartridge.header.cartridge_type.mapper);
+        assert_eq!(0x8000, cartridge.ram.len());
+        assert_eq!(0x12, cartridge.read_rom(0x0150));
+    }
+
+    #[test]
+    fn test_mmm01_menu_first(){
+        let mut rom = test_rom(0x0D, 0x03, 0x03);
+        rom[0x0150] = 0x12;
+        rom[0x38150] = 0x34;
+        fix_checksums(&mut rom);
+
+        let cartridge = Cartridge::new(rom).unwrap();
+
+        assert_eq!(Mapper::Mmm01, cartridge.header.cartridge_type.mapper);
+        assert_eq!(0x12, cartridge.read_rom(0x0150));
+    }
+
+    #[test]
+    fn test_wisdom_tree_header_is_ignored(){
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        rom.resize(4 * 0x8000, 0x00);
+        rom[0x0200..0x020B].copy_from_slice(b"WISDOM\0TREE");
+        rom[3 * 0x8000] = 0x34;
+        let mut cartridge = Cartridge::new(rom).unwrap();
+
+        cartridge.write_rom(0x0003, 0x00);
+
+        assert_eq!(0x34, cartridge.read_rom(0x0000));
+    }
+
+    #[test]
+    fn test_save_file(){
+        let directory = env::temp_dir().join(format!("rustyboy_save_{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let rom_path = directory.join("game.gbc");
+        fs::write(&rom_path, test_rom(0x10, 0x00, 0x02)).unwrap();
+
+        let mut cartridge = Cartridge::from_file(&rom_path).unwrap();
+        cartridge.load_save().unwrap();
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_ram(0xA123, 0x12);
+        cartridge.flush_save().unwrap();
+
+        let save = fs::read(directory.join("game.sav")).unwrap();
+
+        assert_eq!(0x2000 + 48, save.len());
+        assert_eq!(0x12, save[0x123]);
+
+        let mut cartridge = Cartridge::from_file(&rom_path).unwrap();
+        cartridge.load_save().unwrap();
+        cartridge.write_rom(0x0000, 0x0A);
+
+        assert_eq!(0x12, cartridge.read_ram(0xA123));
+
+        cartridge.write_ram(0xA123, 0x34);
+        cartridge.flush_save().unwrap();
+
+        assert_eq!(0x34, fs::read(directory.join("game.sav")).unwrap()[0x123]);
+
+        drop(cartridge);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_save_kept_until_loaded(){
+        let directory = env::temp_dir().join(format!("rustyboy_unloaded_save_{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let rom_path = directory.join("game.gb");
+        let save_path = directory.join("game.sav");
+        fs::write(&rom_path, test_rom(0x03, 0x00, 0x02)).unwrap();
+        fs::write(&save_path, [0x12; 0x2000]).unwrap();
+
+        let cartridge = Cartridge::from_file(&rom_path).unwrap();
+        cartridge.flush_save().unwrap();
+        drop(cartridge);
+
+        assert_eq!(vec![0x12; 0x2000], fs::read(&save_path).unwrap());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_no_save_without_battery(){
+        let directory = env::temp_dir().join(format!("rustyboy_no_save_{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let rom_path = directory.join("game.gb");
+        fs::write(&rom_path, test_rom(0x02, 0x00, 0x02)).unwrap();
+
+        let mut cartridge = Cartridge::from_file(&rom_path).unwrap();
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_ram(0xA000, 0x12);
+        cartridge.flush_save().unwrap();
+
+        assert!(!directory.join("game.sav").exists());
+
+        drop(cartridge);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_no_save_for_m161(){
+        let directory = env::temp_dir().join(format!("rustyboy_m161_{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let rom_path = directory.join("tetris_set.gb");
+        let mut rom = test_rom(0x10, 0x03, 0x03);
+        rom[0x0134..0x013E].copy_from_slice(b"TETRIS SET");
+        fix_checksums(&mut rom);
+        fs::write(&rom_path, rom).unwrap();
+
+        let mut cartridge = Cartridge::from_file(&rom_path).unwrap();
+        cartridge.load_save().unwrap();
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_ram(0xA000, 0x12);
+        cartridge.flush_save().unwrap();
+
+        assert!(!cartridge.has_battery());
+        assert!(cartridge.ram.is_empty());
+        assert_eq!(0xFF, cartridge.read_ram(0xA000));
+        assert!(!directory.join("tetris_set.sav").exists());
+
+        drop(cartridge);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_new_unsupported_mapper(){
+        let result = Cartridge::new(test_rom(0x20, 0x00, 0x00));
+
+        assert!(matches!(result, Err(CartridgeError::UnsupportedMapper(Mapper::Mbc6))));
+    }
+
+    #[test]
+    fn test_new_pads_smaller_image(){
+        let mut rom = test_rom(0x01, 0x01, 0x00);
+        rom.truncate(0x6000);
+        rom[0x5FFF] = 0x12;
+
+        let mut cartridge = Cartridge::new(rom).unwrap();
+        cartridge.write_rom(0x2000, 0x03);
+
+        assert_eq!(0x10000, cartridge.rom.len());
+        assert_eq!(0xFF, cartridge.read_rom(0x4000));
+        assert!(!cartridge.global_checksum_matches());
+
+        cartridge.write_rom(0x2000, 0x01);
+
+        assert_eq!(0x12, cartridge.read_rom(0x5FFF));
+        assert_eq!(0xFF, cartridge.read_rom(0x6000));
+    }
+
+    #[test]
+    fn test_new_keeps_larger_image(){
+        let mut rom = test_rom(0x01, 0x00, 0x00);
+        rom.resize(0x10000, 0x00);
+        rom[0xC000] = 0x12;
+        fix_checksums(&mut rom);
+
+        let mut cartridge = Cartridge::new(rom).unwrap();
+        cartridge.write_rom(0x2000, 0x03);
+
+        assert_eq!(0x12, cartridge.read_rom(0x4000));
+        assert!(cartridge.global_checksum_matches());
+    }
+
+    #[test]
+    fn test_new_global_checksum_mismatch(){
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        rom[0x4000] = 0x01;
+
+        let cartridge = Cartridge::new(rom).unwrap();
+
+        assert!(!cartridge.global_checksum_matches());
+        assert_eq!(0x01, cartridge.read_rom(0x4000));
+    }
+
+    #[test]
+    fn test_from_file_missing(){
+        let result = Cartridge::from_file("does/not/exist.gb");
+
+        let error = result.unwrap_err();
+        assert!(matches!(error, CartridgeError::Io(_)));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn test_error_display(){
+        let error = CartridgeError::HeaderChecksum { expected: 0x12, computed: 0x34 };
+
+        assert_eq!("header checksum mismatch: expected 0x12, computed 0x34", error.to_string());
+    }
+}
@@ -0,0 +1,257 @@
+use crate::core::cartridge::mbc::{Mbc, OPEN_BUS, ram_bank_index, read_rom_bank};
+use crate::core::cartridge::peripheral::Peripheral;
+use crate::core::cartridge::sensor::SensorFrame;
+
+// The part of the sensor image the camera keeps, as 16x14 tiles
+pub(crate) const CAMERA_WIDTH: usize = 128;
+pub(crate) const CAMERA_HEIGHT: usize = 112;
+// Where the captured tiles go in RAM bank 0
+const IMAGE_OFFSET: usize = 0x0100;
+
+// Setting bit 4 of the RAM bank maps the registers instead of the RAM
+const REGISTER_MODE: u8 = 0x10;
+// 0x00 controls the capture, 0x01-0x05 configure the sensor and 0x06-0x35 hold the 4x4 dither
+// matrix, as three thresholds per pixel
+const REGISTER_COUNT: usize = 0x36;
+const DITHER_MATRIX: usize = 0x06;
+const CAPTURE_BIT: u8 = 0x01;
+// Bit 7 of register 0x01 skips part of the readout
+const N_BIT: u8 = 0x80;
+
+// The MAC-GBD controller of the Game Boy Camera, which captures frames from its sensor into RAM
+#[derive(Debug)]
+pub(crate) struct PocketCamera {
+    // The RAM can always be read, only writes need enabling
+    ram_write_enabled: bool,
+    rom_bank: u8,
+    ram_bank: u8,
+    registers: [u8; REGISTER_COUNT],
+    // Cartridge cycles until the capture in progress completes
+    capture_cycles: u64,
+    frame: SensorFrame
+}
+
+impl PocketCamera {
+    pub(crate) fn new() -> Self {
+        PocketCamera {
+            ram_write_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            registers: [0; REGISTER_COUNT],
+            capture_cycles: 0,
+            frame: SensorFrame::uniform(0x80)
+        }
+    }
+
+    fn register_mode(&self) -> bool {
+        self.ram_bank & REGISTER_MODE != 0
+    }
+
+    fn exposure(&self) -> u64 {
+        u16::from_be_bytes([self.registers[0x02], self.registers[0x03]]) as u64
+    }
+
+    // M-cycles at normal speed, which tick twice as many cartridge cycles
+    fn capture_duration(&self) -> u64 {
+        let readout = if self.registers[0x01] & N_BIT != 0 { 0 } else { 512 };
+        2 * (32446 + readout + 16 * self.exposure())
+    }
+
+    fn start_capture(&mut self) {
+        self.capture_cycles = self.capture_duration();
+    }
+
+    // Dithers the frame into 2bpp tiles. The gain, exposure and edge settings only affect the
+    // timing, the frame being taken as what the sensor outputs.
+    fn finish_capture(&mut self, ram: &mut [u8]) {
+        self.registers[0x00] &= !CAPTURE_BIT;
+        if ram.len() < IMAGE_OFFSET + CAMERA_WIDTH * CAMERA_HEIGHT / 4 {
+            return;
+        }
+        for y in 0..CAMERA_HEIGHT {
+            for x in 0..CAMERA_WIDTH {
+                let value = self.frame.sample(x, y, CAMERA_WIDTH, CAMERA_HEIGHT);
+                let thresholds = DITHER_MATRIX + ((y % 4) * 4 + x % 4) * 3;
+                let color = 3 - self.registers[thresholds..thresholds + 3].iter()
+                    .take_while(|threshold| value >= **threshold)
+                    .count() as u8;
+                let tile = (y / 8) * (CAMERA_WIDTH / 8) + x / 8;
+                let index = IMAGE_OFFSET + tile * 16 + (y % 8) * 2;
+                let bit = 0x80 >> (x % 8);
+                for (plane, mask) in [(0, 0x01), (1, 0x02)] {
+                    if color & mask != 0 {
+                        ram[index + plane] |= bit;
+                    } else {
+                        ram[index + plane] &= !bit;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Mbc for PocketCamera {
+    fn read_rom(&self, rom: &[u8], address: u16) -> u8 {
+        match address {
+            0x0000..=0x3FFF => read_rom_bank(rom, 0, address),
+            _ => read_rom_bank(rom, self.rom_bank as usize, address)
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_write_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => self.rom_bank = (value & 0x3F).max(1),
+            0x4000..=0x5FFF => self.ram_bank = value & (REGISTER_MODE | 0x0F),
+            _ => {}
+        }
+    }
+
+    // In register mode only the capture register reads back, the others read 0.
+    // The RAM reads 0 while a capture is in progress.
+    fn read_ram(&self, ram: &[u8], address: u16) -> u8 {
+        if self.register_mode() {
+            return if address & 0x7F == 0x00 { self.registers[0x00] & 0x07 } else { 0x00 };
+        }
+        if self.capture_cycles > 0 {
+            return 0x00;
+        }
+        ram_bank_index(ram, self.ram_bank as usize, address).map_or(OPEN_BUS, |index| ram[index])
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], address: u16, value: u8) {
+        if self.register_mode() {
+            match (address & 0x7F) as usize {
+                0x00 => {
+                    let capturing = self.capture_cycles > 0;
+                    self.registers[0x00] = value & 0x07;
+                    if value & CAPTURE_BIT != 0 && !capturing {
+                        self.start_capture();
+                    } else if capturing {
+                        // The capture can not be stopped once started
+                        self.registers[0x00] |= CAPTURE_BIT;
+                    }
+                }
+                register if register < REGISTER_COUNT => self.registers[register] = value,
+                _ => {}
+            }
+        } else if self.ram_write_enabled && self.capture_cycles == 0 {
+            if let Some(index) = ram_bank_index(ram, self.ram_bank as usize, address) {
+                ram[index] = value;
+            }
+        }
+    }
+
+    fn tick(&mut self, ram: &mut [u8], cycles: u64) {
+        if self.capture_cycles == 0 {
+            return;
+        }
+        self.capture_cycles = self.capture_cycles.saturating_sub(cycles);
+        if self.capture_cycles == 0 {
+            self.finish_capture(ram);
+        }
+    }
+
+    fn connect(&mut self, peripheral: Peripheral) -> Result<(), Peripheral> {
+        match peripheral {
+            Peripheral::CameraFrame(frame) => {
+                self.frame = frame;
+                Ok(())
+            }
+            peripheral => Err(peripheral)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::cartridge::camera::PocketCamera;
+    use crate::core::cartridge::mbc::Mbc;
+    use crate::core::cartridge::peripheral::Peripheral;
+    use crate::core::cartridge::sensor::SensorFrame;
+
+    fn camera_with_matrix(thresholds: [u8; 3]) -> PocketCamera {
+        let mut camera = PocketCamera::new();
+        camera.write_rom(0x4000, 0x10);
+        for pixel in 0..16 {
+            for (index, threshold) in thresholds.iter().enumerate() {
+                camera.write_ram(&mut [], 0xA006 + pixel * 3 + index as u16, *threshold);
+            }
+        }
+        camera
+    }
+
+    #[test]
+    fn test_ram(){
+        let mut ram = vec![0x00; 0x20000];
+        let mut camera = PocketCamera::new();
+        camera.write_rom(0x4000, 0x0F);
+
+        camera.write_ram(&mut ram, 0xA000, 0x12);
+
+        assert_eq!(0x00, camera.read_ram(&ram, 0xA000));
+
+        camera.write_rom(0x0000, 0x0A);
+        camera.write_ram(&mut ram, 0xA000, 0x12);
+
+        assert_eq!(0x12, ram[15 * 0x2000]);
+        assert_eq!(0x12, camera.read_ram(&ram, 0xA000));
+    }
+
+    #[test]
+    fn test_registers(){
+        let mut ram = vec![0x00; 0x20000];
+        let mut camera = PocketCamera::new();
+        camera.write_rom(0x4000, 0x10);
+
+        camera.write_ram(&mut ram, 0xA000, 0x06);
+        camera.write_ram(&mut ram, 0xA002, 0x12);
+
+        assert_eq!(0x06, camera.read_ram(&ram, 0xA000));
+        assert_eq!(0x06, camera.read_ram(&ram, 0xA080));
+        assert_eq!(0x00, camera.read_ram(&ram, 0xA002));
+        assert_eq!(0x00, ram[0x0002]);
+    }
+
+    #[test]
+    fn test_capture_timing(){
+        let mut ram = vec![0x00; 0x20000];
+        let mut camera = PocketCamera::new();
+        camera.write_rom(0x4000, 0x10);
+        camera.write_ram(&mut ram, 0xA001, 0x80);
+        camera.write_ram(&mut ram, 0xA002, 0x01);
+        camera.write_ram(&mut ram, 0xA003, 0x00);
+
+        camera.write_ram(&mut ram, 0xA000, 0x01);
+        camera.write_ram(&mut ram, 0xA000, 0x00);
+        camera.tick(&mut ram, 2 * (32446 + 16 * 0x100) - 1);
+
+        assert_eq!(0x01, camera.read_ram(&ram, 0xA000));
+
+        camera.tick(&mut ram, 1);
+
+        assert_eq!(0x00, camera.read_ram(&ram, 0xA000));
+    }
+
+    #[test]
+    fn test_capture_dithers_frame(){
+        let mut ram = vec![0x00; 0x20000];
+        let mut camera = camera_with_matrix([0x40, 0x80, 0xC0]);
+        let mut pixels = vec![0x00; 128 * 112];
+        pixels[1] = 0x40;
+        pixels[2] = 0x80;
+        pixels[3] = 0xFF;
+        pixels[128 * 111 + 127] = 0xFF;
+        camera.connect(Peripheral::CameraFrame(SensorFrame::new(128, 112, pixels).unwrap())).unwrap();
+
+        camera.write_ram(&mut ram, 0xA000, 0x01);
+        camera.tick(&mut ram, u64::MAX);
+        camera.write_rom(0x4000, 0x00);
+
+        assert_eq!(0b1010_1111, camera.read_ram(&ram, 0xA100));
+        assert_eq!(0b1100_1111, camera.read_ram(&ram, 0xA101));
+        assert_eq!(0b1111_1110, ram[0x0100 + 223 * 16 + 14]);
+        assert_eq!(0b1111_1110, ram[0x0100 + 223 * 16 + 15]);
+        assert_eq!(0x00, ram[0x0100 + 224 * 16]);
+    }
+}
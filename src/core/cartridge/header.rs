@@ -0,0 +1,344 @@
+use crate::core::cartridge::base::CartridgeError;
+
+pub(super) const HEADER_END: usize = 0x0150;
+pub(crate) const LOGO_ADDRESS: usize = 0x0104;
+const TITLE_ADDRESS: usize = 0x0134;
+const MANUFACTURER_CODE_ADDRESS: usize = 0x013F;
+const CGB_FLAG_ADDRESS: usize = 0x0143;
+const NEW_LICENSEE_CODE_ADDRESS: usize = 0x0144;
+const SGB_FLAG_ADDRESS: usize = 0x0146;
+pub(crate) const CARTRIDGE_TYPE_ADDRESS: usize = 0x0147;
+const ROM_SIZE_ADDRESS: usize = 0x0148;
+const RAM_SIZE_ADDRESS: usize = 0x0149;
+const DESTINATION_CODE_ADDRESS: usize = 0x014A;
+const OLD_LICENSEE_CODE_ADDRESS: usize = 0x014B;
+const VERSION_ADDRESS: usize = 0x014C;
+const HEADER_CHECKSUM_ADDRESS: usize = 0x014D;
+const GLOBAL_CHECKSUM_ADDRESS: usize = 0x014E;
+
+// Old licensee code telling the new one is used instead
+const USE_NEW_LICENSEE_CODE: u8 = 0x33;
+
+pub(crate) const ROM_BANK_SIZE: usize = 0x4000;
+
+// Checked by the boot ROM, which locks up if it does not match
+pub(crate) const NINTENDO_LOGO: [u8; 48] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CgbSupport {
+    None,
+    // Works on the DMG too, but uses the CGB features when available
+    Enhanced,
+    Only
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Mapper {
+    None,
+    Mbc1,
+    Mbc2,
+    Mmm01,
+    Mbc3,
+    Mbc5,
+    Mbc6,
+    Mbc7,
+    PocketCamera,
+    Tama5,
+    HuC3,
+    HuC1,
+    // Unlicensed, never named by the header
+    M161,
+    WisdomTree
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct CartridgeType {
+    pub(crate) code: u8,
+    pub(crate) mapper: Mapper,
+    pub(crate) ram: bool,
+    pub(crate) battery: bool,
+    pub(crate) timer: bool,
+    pub(crate) rumble: bool
+}
+
+impl CartridgeType {
+    fn from_code(code: u8) -> Option<Self> {
+        let (mapper, ram, battery, timer, rumble) = match code {
+            0x00 => (Mapper::None, false, false, false, false),
+            0x01 => (Mapper::Mbc1, false, false, false, false),
+            0x02 => (Mapper::Mbc1, true, false, false, false),
+            0x03 => (Mapper::Mbc1, true, true, false, false),
+            0x05 => (Mapper::Mbc2, false, false, false, false),
+            0x06 => (Mapper::Mbc2, false, true, false, false),
+            0x08 => (Mapper::None, true, false, false, false),
+            0x09 => (Mapper::None, true, true, false, false),
+            0x0B => (Mapper::Mmm01, false, false, false, false),
+            0x0C => (Mapper::Mmm01, true, false, false, false),
+            0x0D => (Mapper::Mmm01, true, true, false, false),
+            0x0F => (Mapper::Mbc3, false, true, true, false),
+            0x10 => (Mapper::Mbc3, true, true, true, false),
+            0x11 => (Mapper::Mbc3, false, false, false, false),
+            0x12 => (Mapper::Mbc3, true, false, false, false),
+            0x13 => (Mapper::Mbc3, true, true, false, false),
+            0x19 => (Mapper::Mbc5, false, false, false, false),
+            0x1A => (Mapper::Mbc5, true, false, false, false),
+            0x1B => (Mapper::Mbc5, true, true, false, false),
+            0x1C => (Mapper::Mbc5, false, false, false, true),
+            0x1D => (Mapper::Mbc5, true, false, false, true),
+            0x1E => (Mapper::Mbc5, true, true, false, true),
+            0x20 => (Mapper::Mbc6, true, true, false, false),
+            0x22 => (Mapper::Mbc7, true, true, false, false),
+            0xFC => (Mapper::PocketCamera, true, true, false, false),
+            0xFD => (Mapper::Tama5, true, true, false, false),
+            0xFE => (Mapper::HuC3, true, true, true, false),
+            0xFF => (Mapper::HuC1, true, true, false, false),
+            _ => return None
+        };
+        Some(CartridgeType { code, mapper, ram, battery, timer, rumble })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Header {
+    pub(crate) title: String,
+    // Sum of the 16 bytes from 0x0134 to 0x0143, which the CGB boot ROM uses to color DMG games
+    pub(crate) title_checksum: u8,
+    // Only present in the newer headers, where it takes the last 4 bytes of the title
+    pub(crate) manufacturer_code: Option<String>,
+    pub(crate) cgb_support: CgbSupport,
+    pub(crate) sgb_support: bool,
+    pub(crate) cartridge_type: CartridgeType,
+    pub(crate) rom_size: usize,
+    pub(crate) ram_size: usize,
+    // 0x00 for Japan, 0x01 for everywhere else
+    pub(crate) destination_code: u8,
+    pub(crate) old_licensee_code: u8,
+    // Only meaningful when the old licensee code is 0x33
+    pub(crate) new_licensee_code: Option<String>,
+    pub(crate) version: u8,
+    pub(crate) header_checksum: u8,
+    pub(crate) global_checksum: u16
+}
+
+impl Header {
+    pub(crate) fn parse(rom: &[u8]) -> Result<Self, CartridgeError> {
+        if rom.len() < HEADER_END {
+            return Err(CartridgeError::TooSmall { size: rom.len() });
+        }
+        let computed_header_checksum = header_checksum(rom);
+        let header_checksum = rom[HEADER_CHECKSUM_ADDRESS];
+        if computed_header_checksum != header_checksum {
+            return Err(CartridgeError::HeaderChecksum { expected: header_checksum, computed: computed_header_checksum });
+        }
+
+        let cartridge_type_code = rom[CARTRIDGE_TYPE_ADDRESS];
+        let cartridge_type = CartridgeType::from_code(cartridge_type_code)
+            .ok_or(CartridgeError::UnknownCartridgeType(cartridge_type_code))?;
+        let rom_size_code = rom[ROM_SIZE_ADDRESS];
+        let rom_size = rom_size(rom_size_code).ok_or(CartridgeError::UnknownRomSize(rom_size_code))?;
+        let ram_size_code = rom[RAM_SIZE_ADDRESS];
+        let ram_size = ram_size(ram_size_code).ok_or(CartridgeError::UnknownRamSize(ram_size_code))?;
+
+        let cgb_support = match rom[CGB_FLAG_ADDRESS] {
+            0x80 => CgbSupport::Enhanced,
+            0xC0 => CgbSupport::Only,
+            _ => CgbSupport::None
+        };
+        let manufacturer_code = &rom[MANUFACTURER_CODE_ADDRESS..CGB_FLAG_ADDRESS];
+        let has_manufacturer_code = cgb_support != CgbSupport::None
+            && manufacturer_code.iter().all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit());
+        let title_end = if has_manufacturer_code { MANUFACTURER_CODE_ADDRESS } else { CGB_FLAG_ADDRESS };
+        let old_licensee_code = rom[OLD_LICENSEE_CODE_ADDRESS];
+
+        Ok(Header {
+            title: ascii_string(&rom[TITLE_ADDRESS..title_end]),
+            title_checksum: rom[TITLE_ADDRESS..NEW_LICENSEE_CODE_ADDRESS].iter()
+                .fold(0u8, |checksum, byte| checksum.wrapping_add(*byte)),
+            manufacturer_code: has_manufacturer_code.then(|| ascii_string(manufacturer_code)),
+            cgb_support,
+            sgb_support: rom[SGB_FLAG_ADDRESS] == 0x03,
+            cartridge_type,
+            rom_size,
+            ram_size,
+            destination_code: rom[DESTINATION_CODE_ADDRESS],
+            old_licensee_code,
+            new_licensee_code: (old_licensee_code == USE_NEW_LICENSEE_CODE)
+                .then(|| ascii_string(&rom[NEW_LICENSEE_CODE_ADDRESS..SGB_FLAG_ADDRESS])),
+            version: rom[VERSION_ADDRESS],
+            header_checksum,
+            global_checksum: u16::from_be_bytes([rom[GLOBAL_CHECKSUM_ADDRESS], rom[GLOBAL_CHECKSUM_ADDRESS + 1]])
+        })
+    }
+}
+
+// Computed by the boot ROM over 0x0134-0x014C, which locks up if it does not match
+pub(crate) fn header_checksum(rom: &[u8]) -> u8 {
+    rom[TITLE_ADDRESS..HEADER_CHECKSUM_ADDRESS].iter()
+        .fold(0u8, |checksum, byte| checksum.wrapping_sub(*byte).wrapping_sub(1))
+}
+
+// Sum of every byte in the ROM but the checksum itself
+pub(crate) fn global_checksum(rom: &[u8]) -> u16 {
+    rom.iter().enumerate()
+        .filter(|(address, _)| *address != GLOBAL_CHECKSUM_ADDRESS && *address != GLOBAL_CHECKSUM_ADDRESS + 1)
+        .fold(0u16, |checksum, (_, byte)| checksum.wrapping_add(*byte as u16))
+}
+
+fn rom_size(code: u8) -> Option<usize> {
+    let banks = match code {
+        0x00..=0x08 => 2 << code,
+        0x52 => 72,
+        0x53 => 80,
+        0x54 => 96,
+        _ => return None
+    };
+    Some(banks * ROM_BANK_SIZE)
+}
+
+fn ram_size(code: u8) -> Option<usize> {
+    match code {
+        0x00 => Some(0),
+        0x01 => Some(0x800),
+        0x02 => Some(0x2000),
+        0x03 => Some(0x8000),
+        0x04 => Some(0x20000),
+        0x05 => Some(0x10000),
+        _ => None
+    }
+}
+
+// Stops at the first NUL, as shorter titles are padded with them
+fn ascii_string(bytes: &[u8]) -> String {
+    bytes.iter()
+        .take_while(|byte| **byte != 0x00)
+        .map(|byte| if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '?' })
+        .collect()
+}
+
+// Builds an image of the given size with a valid header, to be tweaked by the tests
+#[cfg(test)]
+pub(crate) fn test_rom(cartridge_type: u8, rom_size_code: u8, ram_size_code: u8) -> Vec<u8> {
+    let mut rom = vec![0x00; rom_size(rom_size_code).unwrap()];
+    rom[TITLE_ADDRESS..TITLE_ADDRESS + 4].copy_from_slice(b"TEST");
+    rom[CARTRIDGE_TYPE_ADDRESS] = cartridge_type;
+    rom[ROM_SIZE_ADDRESS] = rom_size_code;
+    rom[RAM_SIZE_ADDRESS] = ram_size_code;
+    fix_checksums(&mut rom);
+    rom
+}
+
+// Every bank starts with its own number
+#[cfg(test)]
+pub(crate) fn numbered_rom(banks: usize) -> Vec<u8> {
+    let mut rom = vec![0x00; banks * ROM_BANK_SIZE];
+    for bank in 0..banks {
+        rom[bank * ROM_BANK_SIZE] = bank as u8;
+    }
+    rom
+}
+
+#[cfg(test)]
+pub(crate) fn fix_checksums(rom: &mut [u8]) {
+    rom[HEADER_CHECKSUM_ADDRESS] = header_checksum(rom);
+    let [msb, lsb] = global_checksum(rom).to_be_bytes();
+    rom[GLOBAL_CHECKSUM_ADDRESS] = msb;
+    rom[GLOBAL_CHECKSUM_ADDRESS + 1] = lsb;
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::cartridge::base::CartridgeError;
+    use crate::core::cartridge::header::{CgbSupport, fix_checksums, Header, Mapper, test_rom};
+
+    #[test]
+    fn test_parse(){
+        let mut rom = test_rom(0x13, 0x02, 0x03);
+        rom[0x0134..0x013F].copy_from_slice(b"POKEMON YEL");
+        rom[0x013F..0x0143].copy_from_slice(b"APSE");
+        rom[0x0143] = 0x80;
+        rom[0x0144..0x0146].copy_from_slice(b"01");
+        rom[0x0146] = 0x03;
+        rom[0x014A] = 0x01;
+        rom[0x014B] = 0x33;
+        rom[0x014C] = 0x02;
+        fix_checksums(&mut rom);
+
+        let header = Header::parse(&rom).unwrap();
+
+        assert_eq!("POKEMON YEL", header.title);
+        assert_eq!(Some("APSE".to_string()), header.manufacturer_code);
+        assert_eq!(CgbSupport::Enhanced, header.cgb_support);
+        assert!(header.sgb_support);
+        assert_eq!(Mapper::Mbc3, header.cartridge_type.mapper);
+        assert!(header.cartridge_type.ram && header.cartridge_type.battery);
+        assert!(!header.cartridge_type.timer);
+        assert_eq!(0x20000, header.rom_size);
+        assert_eq!(0x8000, header.ram_size);
+        assert_eq!(0x01, header.destination_code);
+        assert_eq!(0x33, header.old_licensee_code);
+        assert_eq!(Some("01".to_string()), header.new_licensee_code);
+        assert_eq!(0x02, header.version);
+    }
+
+    #[test]
+    fn test_parse_old_header(){
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        rom[0x0134..0x0144].copy_from_slice(b"TETRIS\0\0\0\0\0\0\0\0\0\0");
+        rom[0x014B] = 0x01;
+        fix_checksums(&mut rom);
+
+        let header = Header::parse(&rom).unwrap();
+
+        assert_eq!("TETRIS", header.title);
+        assert_eq!(0xDB, header.title_checksum);
+        assert_eq!(None, header.manufacturer_code);
+        assert_eq!(CgbSupport::None, header.cgb_support);
+        assert!(!header.sgb_support);
+        assert_eq!(Mapper::None, header.cartridge_type.mapper);
+        assert_eq!(0x8000, header.rom_size);
+        assert_eq!(0, header.ram_size);
+        assert_eq!(None, header.new_licensee_code);
+    }
+
+    #[test]
+    fn test_parse_too_small(){
+        let result = Header::parse(&[0x00; 0x100]);
+
+        assert!(matches!(result, Err(CartridgeError::TooSmall { size: 0x100 })));
+    }
+
+    #[test]
+    fn test_parse_header_checksum_mismatch(){
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        rom[0x0134] = b'X';
+
+        let result = Header::parse(&rom);
+
+        assert!(matches!(result, Err(CartridgeError::HeaderChecksum { .. })));
+    }
+
+    #[test]
+    fn test_parse_unknown_codes(){
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        rom[0x0147] = 0x04;
+        fix_checksums(&mut rom);
+
+        assert!(matches!(Header::parse(&rom), Err(CartridgeError::UnknownCartridgeType(0x04))));
+
+        rom[0x0147] = 0x00;
+        rom[0x0148] = 0x09;
+        fix_checksums(&mut rom);
+
+        assert!(matches!(Header::parse(&rom), Err(CartridgeError::UnknownRomSize(0x09))));
+
+        rom[0x0148] = 0x00;
+        rom[0x0149] = 0x06;
+        fix_checksums(&mut rom);
+
+        assert!(matches!(Header::parse(&rom), Err(CartridgeError::UnknownRamSize(0x06))));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::core::cartridge::infrared::{IrTransceiver, NoTransceiver, read_ir};
+use crate::core::cartridge::mbc::{Mbc, OPEN_BUS, ram_bank_index, read_rom_bank};
+use crate::core::cartridge::peripheral::Peripheral;
+
+// Value written to 0x0000-0x1FFF to map the infrared port instead of the RAM
+const IR_MODE: u8 = 0x0E;
+
+#[derive(Debug)]
+pub(crate) struct HuC1 {
+    // The RAM needs no enabling, it is mapped whenever the IR port is not
+    ir_mode: bool,
+    rom_bank: u8,
+    ram_bank: u8,
+    transceiver: Box<dyn IrTransceiver>
+}
+
+impl HuC1 {
+    pub(crate) fn new() -> Self {
+        HuC1 {
+            ir_mode: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            transceiver: Box::new(NoTransceiver)
+        }
+    }
+}
+
+impl Mbc for HuC1 {
+    fn read_rom(&self, rom: &[u8], address: u16) -> u8 {
+        match address {
+            0x0000..=0x3FFF => read_rom_bank(rom, 0, address),
+            _ => read_rom_bank(rom, self.rom_bank as usize, address)
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ir_mode = value & 0x0F == IR_MODE,
+            0x2000..=0x3FFF => self.rom_bank = (value & 0x3F).max(1),
+            0x4000..=0x5FFF => self.ram_bank = value & 0x03,
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], address: u16) -> u8 {
+        if self.ir_mode {
+            return read_ir(self.transceiver.as_ref());
+        }
+        ram_bank_index(ram, self.ram_bank as usize, address).map_or(OPEN_BUS, |index| ram[index])
+    }
+
+    // In IR mode, bit 0 turns the LED on
+    fn write_ram(&mut self, ram: &mut [u8], address: u16, value: u8) {
+        if self.ir_mode {
+            self.transceiver.set_led(value & 0x01 != 0);
+        } else if let Some(index) = ram_bank_index(ram, self.ram_bank as usize, address) {
+            ram[index] = value;
+        }
+    }
+
+    fn connect(&mut self, peripheral: Peripheral) -> Result<(), Peripheral> {
+        match peripheral {
+            Peripheral::Infrared(transceiver) => {
+                self.transceiver = transceiver;
+                Ok(())
+            }
+            peripheral => Err(peripheral)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::cartridge::huc1::HuC1;
+    use crate::core::cartridge::infrared::LoopbackTransceiver;
+    use crate::core::cartridge::mbc::Mbc;
+    use crate::core::cartridge::peripheral::Peripheral;
+
+    #[test]
+    fn test_banking(){
+        let mut rom = vec![0x00; 64 * 0x4000];
+        rom[0x3F * 0x4000] = 0x3F;
+        let mut ram = vec![0x00; 0x8000];
+        let mut mbc = HuC1::new();
+
+        mbc.write_rom(0x2000, 0xFF);
+        mbc.write_rom(0x4000, 0x03);
+        mbc.write_ram(&mut ram, 0xA001, 0x12);
+
+        assert_eq!(0x3F, mbc.read_rom(&rom, 0x4000));
+        assert_eq!(0x12, ram[0x6001]);
+        assert_eq!(0x12, mbc.read_ram(&ram, 0xA001));
+    }
+
+    #[test]
+    fn test_ir_mode(){
+        let mut ram = vec![0x00; 0x2000];
+        let mut mbc = HuC1::new();
+        mbc.write_rom(0x0000, 0x0E);
+
+        assert_eq!(0xC0, mbc.read_ram(&ram, 0xA000));
+
+        mbc.connect(Peripheral::Infrared(Box::new(LoopbackTransceiver::default()))).unwrap();
+        mbc.write_ram(&mut ram, 0xA000, 0x01);
+
+        assert_eq!(0xC1, mbc.read_ram(&ram, 0xA000));
+        assert_eq!(0x00, ram[0x0000]);
+
+        mbc.write_ram(&mut ram, 0xA000, 0x00);
+
+        assert_eq!(0xC0, mbc.read_ram(&ram, 0xA000));
+
+        mbc.write_rom(0x0000, 0x0A);
+
+        assert_eq!(0x00, mbc.read_ram(&ram, 0xA000));
+    }
+}
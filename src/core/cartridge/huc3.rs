@@ -0,0 +1,372 @@
+use std::fmt::{Debug, Formatter};
+use crate::core::cartridge::infrared::{IrTransceiver, NoTransceiver, read_ir};
+use crate::core::cartridge::mbc::{Mbc, OPEN_BUS, ram_bank_index, read_rom_bank};
+use crate::core::cartridge::peripheral::Peripheral;
+use crate::core::cartridge::rtc::{CycleTimeSource, TimeSource};
+
+// Called with the new state whenever the tone generator is turned on or off
+pub(crate) type SpeakerCallback = Box<dyn FnMut(bool)>;
+
+// 64 bit UNIX timestamp, then the minutes and days as 16 bit words
+pub(crate) const HUC3_FOOTER_SIZE: usize = 12;
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+// What 0xA000-0xBFFF maps, selected through the lower nibble written to 0x0000-0x1FFF
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    RamReadOnly,
+    Ram,
+    // Takes a command in the upper nibble and its argument in the lower one
+    Command,
+    Response,
+    // Reads 1 once the last command completed, which is always the case here
+    Semaphore,
+    Ir,
+    Unmapped
+}
+
+impl Mode {
+    fn from_value(value: u8) -> Self {
+        match value & 0x0F {
+            0x00 => Mode::RamReadOnly,
+            0x0A => Mode::Ram,
+            0x0B => Mode::Command,
+            0x0C => Mode::Response,
+            0x0D => Mode::Semaphore,
+            0x0E => Mode::Ir,
+            _ => Mode::Unmapped
+        }
+    }
+}
+
+// The clock only counts minutes and days. It is read and set through a 256 nibble scratch memory,
+// where the time goes to 0x00-0x05 as two 12 bit values, least significant nibble first.
+#[derive(Debug)]
+struct Clock {
+    minutes: u16,
+    days: u16,
+    seconds: u64,
+    time_source: Box<dyn TimeSource>
+}
+
+impl Clock {
+    fn advance(&mut self, seconds: u64) {
+        self.seconds += seconds;
+        let minutes = self.minutes as u64 + self.seconds / 60;
+        self.seconds %= 60;
+        self.days = ((self.days as u64 + minutes / MINUTES_PER_DAY as u64) & 0x0FFF) as u16;
+        self.minutes = (minutes % MINUTES_PER_DAY as u64) as u16;
+    }
+}
+
+pub(crate) struct HuC3 {
+    mode: Mode,
+    rom_bank: u8,
+    ram_bank: u8,
+    memory: [u8; 0x100],
+    address: u8,
+    // The last command and its result, as read back in response mode
+    response: u8,
+    clock: Clock,
+    speaker_on: bool,
+    speaker_callback: Option<SpeakerCallback>,
+    transceiver: Box<dyn IrTransceiver>
+}
+
+impl Debug for HuC3 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HuC3")
+            .field("mode", &self.mode)
+            .field("rom_bank", &self.rom_bank)
+            .field("ram_bank", &self.ram_bank)
+            .field("address", &self.address)
+            .field("response", &self.response)
+            .field("clock", &self.clock)
+            .field("speaker_on", &self.speaker_on)
+            .field("transceiver", &self.transceiver)
+            .finish()
+    }
+}
+
+impl HuC3 {
+    pub(crate) fn new() -> Self {
+        HuC3 {
+            mode: Mode::RamReadOnly,
+            rom_bank: 1,
+            ram_bank: 0,
+            memory: [0; 0x100],
+            address: 0,
+            response: 0,
+            clock: Clock { minutes: 0, days: 0, seconds: 0, time_source: Box::new(CycleTimeSource::new(0)) },
+            speaker_on: false,
+            speaker_callback: None,
+            transceiver: Box::new(NoTransceiver)
+        }
+    }
+
+    fn execute(&mut self, value: u8) {
+        let command = (value >> 4) & 0x07;
+        let argument = value & 0x0F;
+        let mut result = 0x00;
+        match command {
+            // Read, then move to the next nibble
+            0x1 => {
+                result = self.memory[self.address as usize];
+                self.address = self.address.wrapping_add(1);
+            }
+            // Write, then move to the next nibble
+            0x3 => {
+                self.memory[self.address as usize] = argument;
+                self.address = self.address.wrapping_add(1);
+            }
+            0x4 => self.address = (self.address & 0xF0) | argument,
+            0x5 => self.address = (self.address & 0x0F) | (argument << 4),
+            0x6 => result = self.execute_extended(argument),
+            _ => {}
+        }
+        self.response = (command << 4) | result;
+    }
+
+    fn execute_extended(&mut self, argument: u8) -> u8 {
+        match argument {
+            // Copies the time into the scratch memory
+            0x0 => {
+                for (index, value) in [self.clock.minutes, self.clock.days].into_iter().enumerate() {
+                    for nibble in 0..3 {
+                        self.memory[index * 3 + nibble] = (value >> (4 * nibble)) as u8 & 0x0F;
+                    }
+                }
+            }
+            // Sets the time from the scratch memory
+            0x1 => {
+                let value = |first: usize| (0..3)
+                    .fold(0u16, |value, nibble| value | (self.memory[first + nibble] as u16) << (4 * nibble));
+                self.clock.minutes = value(0) % MINUTES_PER_DAY;
+                self.clock.days = value(3);
+                self.clock.seconds = 0;
+                self.clock.time_source.reset_subsecond();
+            }
+            // Status, telling the clock is running
+            0x2 => return 0x01,
+            0xE => self.set_speaker(true),
+            0xF => self.set_speaker(false),
+            _ => {}
+        }
+        0x00
+    }
+
+    fn set_speaker(&mut self, on: bool) {
+        if on == self.speaker_on {
+            return;
+        }
+        self.speaker_on = on;
+        if let Some(callback) = &mut self.speaker_callback {
+            callback(on);
+        }
+    }
+}
+
+impl Mbc for HuC3 {
+    fn read_rom(&self, rom: &[u8], address: u16) -> u8 {
+        match address {
+            0x0000..=0x3FFF => read_rom_bank(rom, 0, address),
+            _ => read_rom_bank(rom, self.rom_bank as usize, address)
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.mode = Mode::from_value(value),
+            0x2000..=0x3FFF => self.rom_bank = (value & 0x7F).max(1),
+            0x4000..=0x5FFF => self.ram_bank = value & 0x0F,
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], address: u16) -> u8 {
+        match self.mode {
+            Mode::RamReadOnly | Mode::Ram =>
+                ram_bank_index(ram, self.ram_bank as usize, address).map_or(OPEN_BUS, |index| ram[index]),
+            Mode::Response => 0x80 | self.response,
+            Mode::Semaphore => 0xFF,
+            Mode::Ir => read_ir(self.transceiver.as_ref()),
+            Mode::Command | Mode::Unmapped => OPEN_BUS
+        }
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], address: u16, value: u8) {
+        match self.mode {
+            Mode::Ram => if let Some(index) = ram_bank_index(ram, self.ram_bank as usize, address) {
+                ram[index] = value;
+            },
+            Mode::Command => self.execute(value),
+            Mode::Ir => self.transceiver.set_led(value & 0x01 != 0),
+            _ => {}
+        }
+    }
+
+    fn tick(&mut self, _ram: &mut [u8], cycles: u64) {
+        let seconds = self.clock.time_source.elapsed_seconds(cycles);
+        self.clock.advance(seconds);
+    }
+
+    fn connect(&mut self, peripheral: Peripheral) -> Result<(), Peripheral> {
+        match peripheral {
+            Peripheral::Clock(time_source) => self.clock.time_source = time_source,
+            Peripheral::Speaker(callback) => self.speaker_callback = Some(callback),
+            Peripheral::Infrared(transceiver) => self.transceiver = transceiver,
+            peripheral => return Err(peripheral)
+        }
+        Ok(())
+    }
+
+    fn save_footer(&self) -> Vec<u8> {
+        let mut footer = Vec::with_capacity(HUC3_FOOTER_SIZE);
+        footer.extend_from_slice(&self.clock.time_source.unix_time().to_le_bytes());
+        footer.extend_from_slice(&self.clock.minutes.to_le_bytes());
+        footer.extend_from_slice(&self.clock.days.to_le_bytes());
+        footer
+    }
+
+    // Catches up with the time spent since the save
+    fn load_footer(&mut self, footer: &[u8]) {
+        if footer.len() < HUC3_FOOTER_SIZE {
+            return;
+        }
+        let timestamp = u64::from_le_bytes(footer[0..8].try_into().unwrap());
+        self.clock.minutes = u16::from_le_bytes([footer[8], footer[9]]) % MINUTES_PER_DAY;
+        self.clock.days = u16::from_le_bytes([footer[10], footer[11]]) & 0x0FFF;
+        self.clock.seconds = 0;
+        self.clock.advance(self.clock.time_source.unix_time().saturating_sub(timestamp));
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::core::cartridge::huc3::{HUC3_FOOTER_SIZE, HuC3};
+    use crate::core::cartridge::infrared::LoopbackTransceiver;
+    use crate::core::cartridge::mbc::Mbc;
+    use crate::core::cartridge::peripheral::Peripheral;
+    use crate::core::cartridge::rtc::{CYCLES_PER_SECOND, CycleTimeSource};
+
+    fn command(mbc: &mut HuC3, ram: &mut [u8], value: u8) -> u8 {
+        mbc.write_rom(0x0000, 0x0B);
+        mbc.write_ram(ram, 0xA000, value);
+        mbc.write_rom(0x0000, 0x0C);
+        mbc.read_ram(ram, 0xA000)
+    }
+
+    // Latches the time, then reads the 6 nibbles back from the scratch memory
+    fn read_time(mbc: &mut HuC3, ram: &mut [u8]) -> (u16, u16) {
+        command(mbc, ram, 0x60);
+        command(mbc, ram, 0x40);
+        command(mbc, ram, 0x50);
+        let nibbles: Vec<u16> = (0..6).map(|_| (command(mbc, ram, 0x10) & 0x0F) as u16).collect();
+        (nibbles[0] | nibbles[1] << 4 | nibbles[2] << 8, nibbles[3] | nibbles[4] << 4 | nibbles[5] << 8)
+    }
+
+    #[test]
+    fn test_ram_modes(){
+        let mut ram = vec![0x00; 0x8000];
+        let mut mbc = HuC3::new();
+        mbc.write_rom(0x4000, 0x02);
+
+        mbc.write_ram(&mut ram, 0xA000, 0x12);
+
+        assert_eq!(0x00, mbc.read_ram(&ram, 0xA000));
+
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_ram(&mut ram, 0xA000, 0x12);
+
+        assert_eq!(0x12, ram[0x4000]);
+        assert_eq!(0x12, mbc.read_ram(&ram, 0xA000));
+
+        mbc.write_rom(0x0000, 0x0D);
+
+        assert_eq!(0xFF, mbc.read_ram(&ram, 0xA000));
+    }
+
+    #[test]
+    fn test_scratch_memory(){
+        let mut ram = vec![0x00; 0x2000];
+        let mut mbc = HuC3::new();
+
+        command(&mut mbc, &mut ram, 0x42);
+        command(&mut mbc, &mut ram, 0x51);
+        command(&mut mbc, &mut ram, 0x3A);
+        command(&mut mbc, &mut ram, 0x42);
+
+        assert_eq!(0x9A, command(&mut mbc, &mut ram, 0x10));
+        assert_eq!(0x90, command(&mut mbc, &mut ram, 0x10));
+        assert_eq!(0xE1, command(&mut mbc, &mut ram, 0x62));
+    }
+
+    #[test]
+    fn test_clock(){
+        let mut ram = vec![0x00; 0x2000];
+        let mut mbc = HuC3::new();
+        mbc.connect(Peripheral::Clock(Box::new(CycleTimeSource::new(0)))).unwrap();
+
+        mbc.tick(&mut ram, (24 * 60 * 60 + 61) * CYCLES_PER_SECOND);
+
+        assert_eq!((1, 1), read_time(&mut mbc, &mut ram));
+
+        // Sets the clock to 23:59 on day 0xABC
+        command(&mut mbc, &mut ram, 0x40);
+        command(&mut mbc, &mut ram, 0x50);
+        for nibble in [0xF, 0x9, 0x5, 0xC, 0xB, 0xA] {
+            command(&mut mbc, &mut ram, 0x30 | nibble);
+        }
+        command(&mut mbc, &mut ram, 0x61);
+        mbc.tick(&mut ram, 60 * CYCLES_PER_SECOND);
+
+        assert_eq!((0, 0xABD), read_time(&mut mbc, &mut ram));
+    }
+
+    #[test]
+    fn test_save_footer(){
+        let mut ram = vec![0x00; 0x2000];
+        let mut mbc = HuC3::new();
+        mbc.connect(Peripheral::Clock(Box::new(CycleTimeSource::new(1000)))).unwrap();
+        mbc.tick(&mut ram, 120 * CYCLES_PER_SECOND);
+
+        let footer = mbc.save_footer();
+        let mut loaded = HuC3::new();
+        loaded.connect(Peripheral::Clock(Box::new(CycleTimeSource::new(1120 + 180)))).unwrap();
+        loaded.load_footer(&footer);
+
+        assert_eq!(HUC3_FOOTER_SIZE, footer.len());
+        assert_eq!((5, 0), read_time(&mut loaded, &mut ram));
+    }
+
+    #[test]
+    fn test_speaker(){
+        let mut ram = vec![0x00; 0x2000];
+        let states = Rc::new(RefCell::new(Vec::new()));
+        let recorded_states = states.clone();
+        let mut mbc = HuC3::new();
+        mbc.connect(Peripheral::Speaker(Box::new(move |on| recorded_states.borrow_mut().push(on)))).unwrap();
+
+        command(&mut mbc, &mut ram, 0x6E);
+        command(&mut mbc, &mut ram, 0x6E);
+        command(&mut mbc, &mut ram, 0x6F);
+
+        assert_eq!(vec![true, false], *states.borrow());
+    }
+
+    #[test]
+    fn test_ir_mode(){
+        let mut ram = vec![0x00; 0x2000];
+        let mut mbc = HuC3::new();
+        mbc.connect(Peripheral::Infrared(Box::new(LoopbackTransceiver::default()))).unwrap();
+        mbc.write_rom(0x0000, 0x0E);
+
+        mbc.write_ram(&mut ram, 0xA000, 0x01);
+
+        assert_eq!(0xC1, mbc.read_ram(&ram, 0xA000));
+        assert_eq!(0x00, ram[0x0000]);
+    }
+}
@@ -0,0 +1,42 @@
+use std::fmt::Debug;
+
+// The other side of the infrared port of a cartridge. The cartridge drives the LED and senses
+// whether any light reaches its receiver.
+pub(crate) trait IrTransceiver: Debug {
+    fn set_led(&mut self, on: bool);
+    fn receiving(&self) -> bool;
+}
+
+// Nothing in front of the port, so no light ever comes back
+#[derive(Debug)]
+pub(crate) struct NoTransceiver;
+
+impl IrTransceiver for NoTransceiver {
+    fn set_led(&mut self, _on: bool) {}
+
+    fn receiving(&self) -> bool {
+        false
+    }
+}
+
+// Receives the light of its own LED, as if the port faced a mirror
+#[allow(dead_code)]
+#[derive(Debug, Default)]
+pub(crate) struct LoopbackTransceiver {
+    led_on: bool
+}
+
+impl IrTransceiver for LoopbackTransceiver {
+    fn set_led(&mut self, on: bool) {
+        self.led_on = on;
+    }
+
+    fn receiving(&self) -> bool {
+        self.led_on
+    }
+}
+
+// Read by HuC1 and HuC3 in IR mode, with bit 0 set while light is received
+pub(crate) fn read_ir(transceiver: &dyn IrTransceiver) -> u8 {
+    0xC0 | transceiver.receiving() as u8
+}
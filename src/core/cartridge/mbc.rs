@@ -0,0 +1,87 @@
+use std::fmt::Debug;
+use crate::core::cartridge::header::ROM_BANK_SIZE;
+use crate::core::cartridge::peripheral::Peripheral;
+
+pub(crate) const RAM_BANK_SIZE: usize = 0x2000;
+pub(crate) const OPEN_BUS: u8 = 0xFF;
+
+// The memory bank controller of a cartridge. It decodes the accesses to 0x0000-0x7FFF and
+// 0xA000-0xBFFF, while the cartridge owns the ROM and RAM chips it maps in.
+pub(crate) trait Mbc: Debug {
+    fn read_rom(&self, rom: &[u8], address: u16) -> u8;
+    // Writes to the ROM range never reach the ROM, they set the controller registers
+    fn write_rom(&mut self, address: u16, value: u8);
+    fn read_ram(&self, ram: &[u8], address: u16) -> u8;
+    fn write_ram(&mut self, ram: &mut [u8], address: u16, value: u8);
+    // Advances the hardware on the cartridge, at 2^21 cycles per second whatever the CPU speed
+    fn tick(&mut self, _ram: &mut [u8], _cycles: u64) {}
+    // Hands the peripheral back when the cartridge does not carry the hardware it connects to
+    fn connect(&mut self, peripheral: Peripheral) -> Result<(), Peripheral> {
+        Err(peripheral)
+    }
+    // State kept by the battery besides the RAM, appended to it in the save
+    fn save_footer(&self) -> Vec<u8> {
+        Vec::new()
+    }
+    fn load_footer(&mut self, _footer: &[u8]) {}
+}
+
+// Reads from the given 16KiB bank, ignoring the bank bits the ROM has no pins for
+pub(crate) fn read_rom_bank(rom: &[u8], bank: usize, address: u16) -> u8 {
+    let banks = rom.len() / ROM_BANK_SIZE;
+    rom[(bank % banks) * ROM_BANK_SIZE + (address as usize % ROM_BANK_SIZE)]
+}
+
+// Index of the byte an address in 0xA000-0xBFFF maps to within the given 8KiB bank.
+// Smaller RAM chips repeat over the whole range.
+pub(crate) fn ram_bank_index(ram: &[u8], bank: usize, address: u16) -> Option<usize> {
+    if ram.is_empty() {
+        return None;
+    }
+    Some((bank * RAM_BANK_SIZE + (address - 0xA000) as usize) % ram.len())
+}
+
+// ROM only cartridges, optionally with up to 8KiB of RAM always mapped in
+#[derive(Debug)]
+pub(crate) struct NoMbc;
+
+impl Mbc for NoMbc {
+    fn read_rom(&self, rom: &[u8], address: u16) -> u8 {
+        rom[address as usize]
+    }
+
+    fn write_rom(&mut self, _address: u16, _value: u8) {}
+
+    fn read_ram(&self, ram: &[u8], address: u16) -> u8 {
+        ram_bank_index(ram, 0, address).map_or(OPEN_BUS, |index| ram[index])
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], address: u16, value: u8) {
+        if let Some(index) = ram_bank_index(ram, 0, address) {
+            ram[index] = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::cartridge::mbc::{ram_bank_index, read_rom_bank};
+
+    #[test]
+    fn test_read_rom_bank_wraps(){
+        let mut rom = vec![0x00; 4 * 0x4000];
+        rom[0x4000] = 0x01;
+        rom[0xC123] = 0x03;
+
+        assert_eq!(0x01, read_rom_bank(&rom, 1, 0x4000));
+        assert_eq!(0x01, read_rom_bank(&rom, 5, 0x4000));
+        assert_eq!(0x03, read_rom_bank(&rom, 7, 0x4123));
+    }
+
+    #[test]
+    fn test_ram_bank_index(){
+        assert_eq!(None, ram_bank_index(&[], 0, 0xA000));
+        assert_eq!(Some(0x2001), ram_bank_index(&[0; 0x8000], 1, 0xA001));
+        assert_eq!(Some(0x0001), ram_bank_index(&[0; 0x800], 0, 0xA801));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::core::cartridge::header::{NINTENDO_LOGO, LOGO_ADDRESS, ROM_BANK_SIZE};
+use crate::core::cartridge::mbc::{Mbc, OPEN_BUS, ram_bank_index, read_rom_bank};
+
+// MBC1M boards are only found on 1MiB ROMs
+const MULTICART_ROM_SIZE: usize = 64 * ROM_BANK_SIZE;
+
+#[derive(Debug)]
+pub(crate) struct Mbc1 {
+    ram_enabled: bool,
+    // 5 bit register at 0x2000-0x3FFF, where 0 selects 1
+    bank1: u8,
+    // 2 bit register at 0x4000-0x5FFF, the upper ROM bank bits or the RAM bank
+    bank2: u8,
+    // Set through 0x6000-0x7FFF, applies BANK2 to 0x0000-0x3FFF and to the RAM too
+    advanced_banking: bool,
+    // MBC1M wiring, where BANK2 sits right above the lower 4 bits of BANK1
+    multicart: bool
+}
+
+impl Mbc1 {
+    pub(crate) fn new(multicart: bool) -> Self {
+        Mbc1 {
+            ram_enabled: false,
+            bank1: 1,
+            bank2: 0,
+            advanced_banking: false,
+            multicart
+        }
+    }
+
+    fn bank2_shift(&self) -> u8 {
+        if self.multicart { 4 } else { 5 }
+    }
+
+    fn low_rom_bank(&self) -> usize {
+        if self.advanced_banking {
+            (self.bank2 << self.bank2_shift()) as usize
+        } else {
+            0
+        }
+    }
+
+    fn high_rom_bank(&self) -> usize {
+        let bank1 = if self.multicart { self.bank1 & 0x0F } else { self.bank1 };
+        ((self.bank2 << self.bank2_shift()) | bank1) as usize
+    }
+
+    fn ram_bank(&self) -> usize {
+        if self.advanced_banking { self.bank2 as usize } else { 0 }
+    }
+}
+
+impl Mbc for Mbc1 {
+    fn read_rom(&self, rom: &[u8], address: u16) -> u8 {
+        match address {
+            0x0000..=0x3FFF => read_rom_bank(rom, self.low_rom_bank(), address),
+            _ => read_rom_bank(rom, self.high_rom_bank(), address)
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            // Only a 0 in all the 5 bits becomes 1, which is why banks 0x20, 0x40 and 0x60
+            // cannot be mapped at 0x4000
+            0x2000..=0x3FFF => self.bank1 = (value & 0x1F).max(1),
+            0x4000..=0x5FFF => self.bank2 = value & 0x03,
+            _ => self.advanced_banking = value & 0x01 != 0
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], address: u16) -> u8 {
+        if !self.ram_enabled {
+            return OPEN_BUS;
+        }
+        ram_bank_index(ram, self.ram_bank(), address).map_or(OPEN_BUS, |index| ram[index])
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], address: u16, value: u8) {
+        if !self.ram_enabled {
+            return;
+        }
+        if let Some(index) = ram_bank_index(ram, self.ram_bank(), address) {
+            ram[index] = value;
+        }
+    }
+}
+
+// MBC1M carts hold a game per 256KiB, so the logo shows up again in the header of bank 0x10
+pub(crate) fn is_multicart(rom: &[u8]) -> bool {
+    let second_logo = 0x10 * ROM_BANK_SIZE + LOGO_ADDRESS;
+    rom.len() == MULTICART_ROM_SIZE && rom[second_logo..second_logo + NINTENDO_LOGO.len()] == NINTENDO_LOGO
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::cartridge::header::{LOGO_ADDRESS, NINTENDO_LOGO, numbered_rom};
+    use crate::core::cartridge::mbc::Mbc;
+    use crate::core::cartridge::mbc1::{is_multicart, Mbc1};
+
+    #[test]
+    fn test_rom_banking(){
+        let rom = numbered_rom(128);
+        let mut mbc = Mbc1::new(false);
+
+        assert_eq!(0x00, mbc.read_rom(&rom, 0x0000));
+        assert_eq!(0x01, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x2000, 0x05);
+
+        assert_eq!(0x05, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x3FFF, 0x00);
+
+        assert_eq!(0x01, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x2000, 0xE3);
+        mbc.write_rom(0x4000, 0x02);
+
+        assert_eq!(0x43, mbc.read_rom(&rom, 0x4000));
+        assert_eq!(0x00, mbc.read_rom(&rom, 0x0000));
+    }
+
+    #[test]
+    fn test_bank_zero_quirk(){
+        let rom = numbered_rom(128);
+        let mut mbc = Mbc1::new(false);
+
+        for (bank2, bank) in [(1, 0x21), (2, 0x41), (3, 0x61)] {
+            mbc.write_rom(0x4000, bank2);
+            mbc.write_rom(0x2000, 0x00);
+
+            assert_eq!(bank, mbc.read_rom(&rom, 0x4000));
+        }
+    }
+
+    #[test]
+    fn test_advanced_banking(){
+        let rom = numbered_rom(128);
+        let mut ram = vec![0x00; 0x8000];
+        let mut mbc = Mbc1::new(false);
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x4000, 0x02);
+        mbc.write_ram(&mut ram, 0xA000, 0x12);
+
+        assert_eq!(0x12, ram[0x0000]);
+        assert_eq!(0x00, mbc.read_rom(&rom, 0x0000));
+
+        mbc.write_rom(0x6000, 0x01);
+        mbc.write_ram(&mut ram, 0xA000, 0x34);
+
+        assert_eq!(0x34, ram[0x4000]);
+        assert_eq!(0x40, mbc.read_rom(&rom, 0x0000));
+        assert_eq!(0x41, mbc.read_rom(&rom, 0x4000));
+    }
+
+    #[test]
+    fn test_small_rom_ignores_upper_bits(){
+        let rom = numbered_rom(8);
+        let mut mbc = Mbc1::new(false);
+
+        mbc.write_rom(0x2000, 0x1D);
+
+        assert_eq!(0x05, mbc.read_rom(&rom, 0x4000));
+    }
+
+    #[test]
+    fn test_ram_enable(){
+        let mut ram = vec![0x00; 0x2000];
+        let mut mbc = Mbc1::new(false);
+        mbc.write_ram(&mut ram, 0xA000, 0x12);
+
+        assert_eq!(0xFF, mbc.read_ram(&ram, 0xA000));
+        assert_eq!(0x00, ram[0x0000]);
+
+        mbc.write_rom(0x1FFF, 0x3A);
+        mbc.write_ram(&mut ram, 0xA000, 0x12);
+
+        assert_eq!(0x12, mbc.read_ram(&ram, 0xA000));
+
+        mbc.write_rom(0x0000, 0x00);
+
+        assert_eq!(0xFF, mbc.read_ram(&ram, 0xA000));
+    }
+
+    #[test]
+    fn test_multicart(){
+        let mut rom = numbered_rom(64);
+        let mut mbc = Mbc1::new(true);
+
+        mbc.write_rom(0x2000, 0x12);
+        mbc.write_rom(0x4000, 0x01);
+
+        assert_eq!(0x12, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x6000, 0x01);
+
+        assert_eq!(0x10, mbc.read_rom(&rom, 0x0000));
+
+        assert!(!is_multicart(&rom));
+
+        let second_logo = 0x10 * 0x4000 + LOGO_ADDRESS;
+        rom[second_logo..second_logo + NINTENDO_LOGO.len()].copy_from_slice(&NINTENDO_LOGO);
+
+        assert!(is_multicart(&rom));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::core::cartridge::mbc::{Mbc, OPEN_BUS, read_rom_bank};
+
+// 512 half-bytes, built into the controller itself
+pub(crate) const MBC2_RAM_SIZE: usize = 0x200;
+
+#[derive(Debug)]
+pub(crate) struct Mbc2 {
+    ram_enabled: bool,
+    rom_bank: u8
+}
+
+impl Mbc2 {
+    pub(crate) fn new() -> Self {
+        Mbc2 {
+            ram_enabled: false,
+            rom_bank: 1
+        }
+    }
+}
+
+// Only the lower 9 address bits reach the RAM, so it repeats over 0xA000-0xBFFF
+fn ram_index(address: u16) -> usize {
+    address as usize % MBC2_RAM_SIZE
+}
+
+impl Mbc for Mbc2 {
+    fn read_rom(&self, rom: &[u8], address: u16) -> u8 {
+        match address {
+            0x0000..=0x3FFF => read_rom_bank(rom, 0, address),
+            _ => read_rom_bank(rom, self.rom_bank as usize, address)
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        if address > 0x3FFF {
+            return;
+        }
+        // Bit 8 of the address picks the register
+        if address & 0x0100 == 0 {
+            self.ram_enabled = value & 0x0F == 0x0A;
+        } else {
+            self.rom_bank = (value & 0x0F).max(1);
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], address: u16) -> u8 {
+        if !self.ram_enabled {
+            return OPEN_BUS;
+        }
+        // Only the lower nibble is stored, the upper one floats high
+        0xF0 | ram[ram_index(address)]
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], address: u16, value: u8) {
+        if self.ram_enabled {
+            ram[ram_index(address)] = value & 0x0F;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::cartridge::mbc::Mbc;
+    use crate::core::cartridge::mbc2::{Mbc2, MBC2_RAM_SIZE};
+
+    #[test]
+    fn test_register_select(){
+        let mut rom = vec![0x00; 16 * 0x4000];
+        rom[5 * 0x4000] = 0x05;
+        rom[0x4000] = 0x01;
+        let mut mbc = Mbc2::new();
+
+        mbc.write_rom(0x2000, 0x05);
+
+        assert_eq!(0x01, mbc.read_rom(&rom, 0x4000));
+        assert!(!mbc.ram_enabled);
+
+        mbc.write_rom(0x2100, 0x05);
+
+        assert_eq!(0x05, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x3EFF, 0x0A);
+
+        assert!(mbc.ram_enabled);
+        assert_eq!(0x05, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x0100, 0x00);
+
+        assert_eq!(0x01, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x4100, 0x03);
+
+        assert_eq!(0x01, mbc.read_rom(&rom, 0x4000));
+    }
+
+    #[test]
+    fn test_ram(){
+        let mut ram = vec![0x00; MBC2_RAM_SIZE];
+        let mut mbc = Mbc2::new();
+        mbc.write_ram(&mut ram, 0xA000, 0x12);
+
+        assert_eq!(0xFF, mbc.read_ram(&ram, 0xA000));
+
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_ram(&mut ram, 0xA000, 0x12);
+        mbc.write_ram(&mut ram, 0xA1FF, 0xAB);
+
+        assert_eq!(0xF2, mbc.read_ram(&ram, 0xA000));
+        assert_eq!(0xFB, mbc.read_ram(&ram, 0xA1FF));
+        assert_eq!(0xF2, mbc.read_ram(&ram, 0xA200));
+        assert_eq!(0xFB, mbc.read_ram(&ram, 0xBFFF));
+    }
+}
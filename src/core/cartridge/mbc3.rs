@@ -0,0 +1,185 @@
+use crate::core::cartridge::mbc::{Mbc, OPEN_BUS, ram_bank_index, read_rom_bank};
+use crate::core::cartridge::peripheral::Peripheral;
+use crate::core::cartridge::rtc::{CycleTimeSource, Rtc};
+
+#[derive(Debug)]
+pub(crate) struct Mbc3 {
+    // Enables the clock registers too
+    ram_enabled: bool,
+    rom_bank: u8,
+    // 0x00-0x07 map a RAM bank, 0x08-0x0C a clock register
+    ram_bank: u8,
+    // Whether 0x00 was the last value written to the latch register
+    latch_armed: bool,
+    rtc: Option<Rtc>
+}
+
+impl Mbc3 {
+    pub(crate) fn new(has_rtc: bool) -> Self {
+        Mbc3 {
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            latch_armed: false,
+            rtc: has_rtc.then(|| Rtc::new(Box::new(CycleTimeSource::new(0))))
+        }
+    }
+}
+
+impl Mbc for Mbc3 {
+    fn read_rom(&self, rom: &[u8], address: u16) -> u8 {
+        match address {
+            0x0000..=0x3FFF => read_rom_bank(rom, 0, address),
+            _ => read_rom_bank(rom, self.rom_bank as usize, address)
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => self.rom_bank = (value & 0x7F).max(1),
+            0x4000..=0x5FFF => self.ram_bank = value & 0x0F,
+            _ => {
+                // Writing 0x00 then 0x01 copies the clock into the latched registers
+                if self.latch_armed && value == 0x01 {
+                    if let Some(rtc) = &mut self.rtc {
+                        rtc.latch();
+                    }
+                }
+                self.latch_armed = value == 0x00;
+            }
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], address: u16) -> u8 {
+        if !self.ram_enabled {
+            return OPEN_BUS;
+        }
+        match (self.ram_bank, &self.rtc) {
+            (0x00..=0x07, _) => ram_bank_index(ram, self.ram_bank as usize, address).map_or(OPEN_BUS, |index| ram[index]),
+            (0x08..=0x0C, Some(rtc)) => rtc.read(self.ram_bank),
+            _ => OPEN_BUS
+        }
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], address: u16, value: u8) {
+        if !self.ram_enabled {
+            return;
+        }
+        match (self.ram_bank, &mut self.rtc) {
+            (0x00..=0x07, _) => if let Some(index) = ram_bank_index(ram, self.ram_bank as usize, address) {
+                ram[index] = value;
+            },
+            (0x08..=0x0C, Some(rtc)) => rtc.write(self.ram_bank, value),
+            _ => {}
+        }
+    }
+
+    fn tick(&mut self, _ram: &mut [u8], cycles: u64) {
+        if let Some(rtc) = &mut self.rtc {
+            rtc.tick(cycles);
+        }
+    }
+
+    fn connect(&mut self, peripheral: Peripheral) -> Result<(), Peripheral> {
+        match (peripheral, &mut self.rtc) {
+            (Peripheral::Clock(time_source), Some(rtc)) => {
+                rtc.set_time_source(time_source);
+                Ok(())
+            }
+            (peripheral, _) => Err(peripheral)
+        }
+    }
+
+    fn save_footer(&self) -> Vec<u8> {
+        self.rtc.as_ref().map_or(Vec::new(), |rtc| rtc.save())
+    }
+
+    fn load_footer(&mut self, footer: &[u8]) {
+        if let Some(rtc) = &mut self.rtc {
+            rtc.load(footer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::cartridge::mbc::Mbc;
+    use crate::core::cartridge::peripheral::Peripheral;
+    use crate::core::cartridge::mbc3::Mbc3;
+    use crate::core::cartridge::rtc::{CYCLES_PER_SECOND, CycleTimeSource};
+
+    #[test]
+    fn test_banking(){
+        let mut rom = vec![0x00; 128 * 0x4000];
+        rom[0x7F * 0x4000] = 0x7F;
+        let mut ram = vec![0x00; 0x8000];
+        let mut mbc = Mbc3::new(false);
+
+        mbc.write_rom(0x2000, 0xFF);
+
+        assert_eq!(0x7F, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x4000, 0x03);
+        mbc.write_ram(&mut ram, 0xA001, 0x12);
+
+        assert_eq!(0x12, ram[0x6001]);
+        assert_eq!(0x12, mbc.read_ram(&ram, 0xA001));
+
+        mbc.write_rom(0x4000, 0x08);
+
+        assert_eq!(0xFF, mbc.read_ram(&ram, 0xA001));
+    }
+
+    #[test]
+    fn test_rtc_registers(){
+        let mut ram = vec![0x00; 0x2000];
+        let mut mbc = Mbc3::new(true);
+        mbc.connect(Peripheral::Clock(Box::new(CycleTimeSource::new(0)))).unwrap();
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x4000, 0x09);
+        mbc.write_ram(&mut ram, 0xA000, 59);
+        mbc.write_rom(0x4000, 0x08);
+        mbc.write_ram(&mut ram, 0xA000, 58);
+
+        mbc.tick(&mut ram, 2 * CYCLES_PER_SECOND);
+
+        assert_eq!(0x00, mbc.read_ram(&ram, 0xA000));
+
+        mbc.write_rom(0x6000, 0x01);
+
+        assert_eq!(0x00, mbc.read_ram(&ram, 0xA000));
+
+        mbc.write_rom(0x6000, 0x00);
+        mbc.write_rom(0x6000, 0x01);
+
+        assert_eq!(0x00, mbc.read_ram(&ram, 0xA000));
+        mbc.write_rom(0x4000, 0x09);
+        assert_eq!(0x00, mbc.read_ram(&ram, 0xA000));
+        mbc.write_rom(0x4000, 0x0A);
+        assert_eq!(0x01, mbc.read_ram(&ram, 0xA000));
+        assert_eq!(0x00, ram[0x0000]);
+    }
+
+    #[test]
+    fn test_rtc_save(){
+        let mut ram = vec![0x00; 0x2000];
+        let mut mbc = Mbc3::new(true);
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x4000, 0x0B);
+        mbc.write_ram(&mut ram, 0xA000, 0x42);
+
+        let footer = mbc.save_footer();
+        let mut loaded = Mbc3::new(true);
+        loaded.load_footer(&footer);
+        loaded.write_rom(0x0000, 0x0A);
+        loaded.write_rom(0x4000, 0x0B);
+        loaded.write_rom(0x6000, 0x00);
+        loaded.write_rom(0x6000, 0x01);
+
+        assert_eq!(48, footer.len());
+        assert_eq!(0x42, loaded.read_ram(&ram, 0xA000));
+        assert!(Mbc3::new(false).save_footer().is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt::{Debug, Formatter};
+use crate::core::cartridge::mbc::{Mbc, OPEN_BUS, ram_bank_index, read_rom_bank};
+use crate::core::cartridge::peripheral::Peripheral;
+
+// Called with the new motor state whenever a rumble cartridge turns its motor on or off
+pub(crate) type RumbleCallback = Box<dyn FnMut(bool)>;
+
+const RUMBLE_BIT: u8 = 0x08;
+
+struct Rumble {
+    motor_on: bool,
+    callback: Option<RumbleCallback>
+}
+
+impl Debug for Rumble {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rumble")
+            .field("motor_on", &self.motor_on)
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
+
+impl Rumble {
+    fn set_motor(&mut self, motor_on: bool) {
+        if motor_on == self.motor_on {
+            return;
+        }
+        self.motor_on = motor_on;
+        if let Some(callback) = &mut self.callback {
+            callback(motor_on);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Mbc5 {
+    ram_enabled: bool,
+    // 9 bits, split between 0x2000-0x2FFF and 0x3000-0x3FFF. Unlike older MBCs, 0 maps bank 0
+    rom_bank: u16,
+    ram_bank: u8,
+    // On rumble cartridges bit 3 of the RAM bank drives the motor instead
+    rumble: Option<Rumble>
+}
+
+impl Mbc5 {
+    pub(crate) fn new(has_rumble: bool) -> Self {
+        Mbc5 {
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            rumble: has_rumble.then_some(Rumble { motor_on: false, callback: None })
+        }
+    }
+}
+
+impl Mbc for Mbc5 {
+    fn read_rom(&self, rom: &[u8], address: u16) -> u8 {
+        match address {
+            0x0000..=0x3FFF => read_rom_bank(rom, 0, address),
+            _ => read_rom_bank(rom, self.rom_bank as usize, address)
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = value == 0x0A,
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | value as u16,
+            0x3000..=0x3FFF => self.rom_bank = (self.rom_bank & 0xFF) | ((value as u16 & 0x01) << 8),
+            0x4000..=0x5FFF => match &mut self.rumble {
+                Some(rumble) => {
+                    rumble.set_motor(value & RUMBLE_BIT != 0);
+                    self.ram_bank = value & 0x07;
+                }
+                None => self.ram_bank = value & 0x0F
+            },
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], address: u16) -> u8 {
+        if !self.ram_enabled {
+            return OPEN_BUS;
+        }
+        ram_bank_index(ram, self.ram_bank as usize, address).map_or(OPEN_BUS, |index| ram[index])
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], address: u16, value: u8) {
+        if !self.ram_enabled {
+            return;
+        }
+        if let Some(index) = ram_bank_index(ram, self.ram_bank as usize, address) {
+            ram[index] = value;
+        }
+    }
+
+    fn connect(&mut self, peripheral: Peripheral) -> Result<(), Peripheral> {
+        match (peripheral, &mut self.rumble) {
+            (Peripheral::Rumble(callback), Some(rumble)) => {
+                rumble.callback = Some(callback);
+                Ok(())
+            }
+            (peripheral, _) => Err(peripheral)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::core::cartridge::mbc::Mbc;
+    use crate::core::cartridge::peripheral::Peripheral;
+    use crate::core::cartridge::mbc5::Mbc5;
+
+    #[test]
+    fn test_rom_banking(){
+        let mut rom = vec![0x00; 512 * 0x4000];
+        for bank in [0x000, 0x0FF, 0x100, 0x1FF] {
+            rom[bank * 0x4000] = (bank >> 1) as u8;
+        }
+        let mut mbc = Mbc5::new(false);
+
+        mbc.write_rom(0x2000, 0x00);
+
+        assert_eq!(0x00, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x2FFF, 0xFF);
+
+        assert_eq!(0x7F, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x3000, 0x01);
+
+        assert_eq!(0xFF, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x2000, 0x00);
+
+        assert_eq!(0x80, mbc.read_rom(&rom, 0x4000));
+        assert_eq!(0x00, mbc.read_rom(&rom, 0x0000));
+    }
+
+    #[test]
+    fn test_ram_banking(){
+        let mut ram = vec![0x00; 16 * 0x2000];
+        let mut mbc = Mbc5::new(false);
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x4000, 0x0F);
+
+        mbc.write_ram(&mut ram, 0xA000, 0x12);
+
+        assert_eq!(0x12, ram[15 * 0x2000]);
+
+        mbc.write_rom(0x0000, 0x1A);
+
+        assert_eq!(0xFF, mbc.read_ram(&ram, 0xA000));
+    }
+
+    #[test]
+    fn test_rumble(){
+        let mut ram = vec![0x00; 8 * 0x2000];
+        let motor_states = Rc::new(RefCell::new(Vec::new()));
+        let recorded_states = motor_states.clone();
+        let mut mbc = Mbc5::new(true);
+        mbc.connect(Peripheral::Rumble(Box::new(move |motor_on| recorded_states.borrow_mut().push(motor_on)))).unwrap();
+        mbc.write_rom(0x0000, 0x0A);
+
+        mbc.write_rom(0x4000, 0x0B);
+        mbc.write_rom(0x4000, 0x0A);
+        mbc.write_ram(&mut ram, 0xA000, 0x12);
+
+        assert_eq!(vec![true], *motor_states.borrow());
+        assert_eq!(0x12, ram[2 * 0x2000]);
+
+        mbc.write_rom(0x4000, 0x02);
+
+        assert_eq!(vec![true, false], *motor_states.borrow());
+    }
+}
@@ -0,0 +1,386 @@
+use crate::core::cartridge::mbc::{Mbc, OPEN_BUS, read_rom_bank};
+use crate::core::cartridge::peripheral::Peripheral;
+
+// A 93LC56 organized as 128 16-bit words, which takes the place of the RAM
+pub(crate) const MBC7_EEPROM_SIZE: usize = 0x100;
+
+// Accelerometer reading when the console lies flat, and how much 1g moves it by
+const ACCELEROMETER_CENTER: f32 = 0x81D0 as f32;
+const ACCELEROMETER_GRAVITY: f32 = 0x70 as f32;
+const ACCELEROMETER_ERASED: u16 = 0x8000;
+
+const EEPROM_CHIP_SELECT: u8 = 0x80;
+const EEPROM_CLOCK: u8 = 0x40;
+const EEPROM_DATA_IN: u8 = 0x02;
+const EEPROM_DATA_OUT: u8 = 0x01;
+// Opcode and address bits following the start bit
+const EEPROM_COMMAND_BITS: u8 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EepromState {
+    // Waiting for the start bit, leading zeros are ignored
+    Idle,
+    Command { command: u16, bits: u8 },
+    // Shifting a word out, moving on to the next one once done
+    Reading { address: u8, bits: u8 },
+    // Shifting the word to write in. Without an address, it goes to every word.
+    Writing { address: Option<u8>, data: u16, bits: u8 },
+    // Until chip select goes low
+    Done
+}
+
+// Bit-banged through 0xAx8x, where bit 7 is chip select, bit 6 the clock, bit 1 data in and
+// bit 0 data out. Bits are shifted in and out on the rising edges of the clock, MSB first.
+#[derive(Debug)]
+struct Eeprom {
+    pins: u8,
+    data_out: bool,
+    write_enabled: bool,
+    state: EepromState
+}
+
+impl Eeprom {
+    fn new() -> Self {
+        Eeprom {
+            pins: 0,
+            data_out: true,
+            write_enabled: false,
+            state: EepromState::Idle
+        }
+    }
+
+    fn read(&self) -> u8 {
+        (self.pins & (EEPROM_CHIP_SELECT | EEPROM_CLOCK | EEPROM_DATA_IN)) | if self.data_out { EEPROM_DATA_OUT } else { 0x00 }
+    }
+
+    fn write(&mut self, memory: &mut [u8], value: u8) {
+        let rising_edge = self.pins & EEPROM_CLOCK == 0 && value & EEPROM_CLOCK != 0;
+        self.pins = value;
+        if value & EEPROM_CHIP_SELECT == 0 {
+            self.state = EepromState::Idle;
+            self.data_out = true;
+        } else if rising_edge {
+            self.shift(memory, value & EEPROM_DATA_IN != 0);
+        }
+    }
+
+    fn shift(&mut self, memory: &mut [u8], data_in: bool) {
+        self.state = match self.state {
+            EepromState::Idle if data_in => EepromState::Command { command: 0, bits: 0 },
+            EepromState::Command { command, bits } => {
+                let command = command << 1 | data_in as u16;
+                if bits + 1 == EEPROM_COMMAND_BITS {
+                    self.execute(memory, command)
+                } else {
+                    EepromState::Command { command, bits: bits + 1 }
+                }
+            }
+            EepromState::Reading { address, bits } => {
+                self.data_out = read_word(memory, address) & (0x8000 >> bits) != 0;
+                match bits + 1 {
+                    16 => EepromState::Reading { address: (address + 1) & 0x7F, bits: 0 },
+                    bits => EepromState::Reading { address, bits }
+                }
+            }
+            EepromState::Writing { address, data, bits } => {
+                let data = data << 1 | data_in as u16;
+                if bits + 1 < 16 {
+                    EepromState::Writing { address, data, bits: bits + 1 }
+                } else {
+                    match address {
+                        Some(address) => self.write_words(memory, address..=address, data),
+                        None => self.write_words(memory, 0..=0x7F, data)
+                    }
+                    EepromState::Done
+                }
+            }
+            state => state
+        };
+    }
+
+    fn execute(&mut self, memory: &mut [u8], command: u16) -> EepromState {
+        let address = (command & 0x7F) as u8;
+        match (command >> 8, (command >> 6) & 0x03) {
+            // READ, which first shifts out a dummy 0
+            (0b10, _) => {
+                self.data_out = false;
+                EepromState::Reading { address, bits: 0 }
+            }
+            (0b01, _) => EepromState::Writing { address: Some(address), data: 0, bits: 0 },
+            (0b11, _) => {
+                self.write_words(memory, address..=address, 0xFFFF);
+                EepromState::Done
+            }
+            // EWDS
+            (0b00, 0b00) => {
+                self.write_enabled = false;
+                EepromState::Done
+            }
+            // WRAL
+            (0b00, 0b01) => EepromState::Writing { address: None, data: 0, bits: 0 },
+            // ERAL
+            (0b00, 0b10) => {
+                self.write_words(memory, 0..=0x7F, 0xFFFF);
+                EepromState::Done
+            }
+            // EWEN
+            _ => {
+                self.write_enabled = true;
+                EepromState::Done
+            }
+        }
+    }
+
+    // Writes complete right away, so data out reports the chip as ready
+    fn write_words(&mut self, memory: &mut [u8], addresses: impl Iterator<Item = u8>, data: u16) {
+        if self.write_enabled {
+            for address in addresses {
+                let index = 2 * address as usize;
+                memory[index..index + 2].copy_from_slice(&data.to_le_bytes());
+            }
+        }
+        self.data_out = true;
+    }
+}
+
+fn read_word(memory: &[u8], address: u8) -> u16 {
+    let index = 2 * address as usize;
+    u16::from_le_bytes([memory[index], memory[index + 1]])
+}
+
+#[derive(Debug)]
+pub(crate) struct Mbc7 {
+    // Both need to be set for 0xA000-0xAFFF to respond
+    ram_enabled: bool,
+    registers_enabled: bool,
+    rom_bank: u8,
+    // Tilt in g reported by the host, positive when tilting right and towards the player
+    tilt: (f32, f32),
+    latched: (u16, u16),
+    // Writing 0x55 erases the latched values, which a write of 0xAA then replaces
+    latch_armed: bool,
+    eeprom: Eeprom
+}
+
+impl Mbc7 {
+    pub(crate) fn new() -> Self {
+        Mbc7 {
+            ram_enabled: false,
+            registers_enabled: false,
+            rom_bank: 1,
+            tilt: (0.0, 0.0),
+            latched: (ACCELEROMETER_ERASED, ACCELEROMETER_ERASED),
+            latch_armed: false,
+            eeprom: Eeprom::new()
+        }
+    }
+
+    fn accessible(&self, address: u16) -> bool {
+        self.ram_enabled && self.registers_enabled && address < 0xB000
+    }
+}
+
+fn accelerometer_value(tilt: f32) -> u16 {
+    (ACCELEROMETER_CENTER + tilt.clamp(-1.0, 1.0) * ACCELEROMETER_GRAVITY) as u16
+}
+
+impl Mbc for Mbc7 {
+    fn read_rom(&self, rom: &[u8], address: u16) -> u8 {
+        match address {
+            0x0000..=0x3FFF => read_rom_bank(rom, 0, address),
+            _ => read_rom_bank(rom, self.rom_bank as usize, address)
+        }
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = value == 0x0A,
+            0x2000..=0x3FFF => self.rom_bank = value & 0x7F,
+            0x4000..=0x5FFF => self.registers_enabled = value == 0x40,
+            _ => {}
+        }
+    }
+
+    // The register is picked by bits 4-7 of the address
+    fn read_ram(&self, _ram: &[u8], address: u16) -> u8 {
+        if !self.accessible(address) {
+            return OPEN_BUS;
+        }
+        let (x, y) = self.latched;
+        match address & 0xF0 {
+            0x20 => x as u8,
+            0x30 => (x >> 8) as u8,
+            0x40 => y as u8,
+            0x50 => (y >> 8) as u8,
+            0x60 => 0x00,
+            0x80 => self.eeprom.read(),
+            _ => OPEN_BUS
+        }
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], address: u16, value: u8) {
+        if !self.accessible(address) {
+            return;
+        }
+        match address & 0xF0 {
+            0x00 if value == 0x55 => {
+                self.latched = (ACCELEROMETER_ERASED, ACCELEROMETER_ERASED);
+                self.latch_armed = true;
+            }
+            0x10 if value == 0xAA && self.latch_armed => {
+                self.latched = (accelerometer_value(self.tilt.0), accelerometer_value(self.tilt.1));
+                self.latch_armed = false;
+            }
+            0x80 => self.eeprom.write(ram, value),
+            _ => {}
+        }
+    }
+
+    fn connect(&mut self, peripheral: Peripheral) -> Result<(), Peripheral> {
+        match peripheral {
+            Peripheral::Tilt { x, y } => {
+                self.tilt = (x, y);
+                Ok(())
+            }
+            peripheral => Err(peripheral)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::cartridge::mbc::Mbc;
+    use crate::core::cartridge::peripheral::Peripheral;
+    use crate::core::cartridge::mbc7::{MBC7_EEPROM_SIZE, Mbc7};
+
+    fn enabled_mbc() -> Mbc7 {
+        let mut mbc = Mbc7::new();
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x4000, 0x40);
+        mbc
+    }
+
+    fn send_bits(mbc: &mut Mbc7, eeprom: &mut [u8], bits: &[u8]) {
+        for bit in bits {
+            mbc.write_ram(eeprom, 0xA080, 0x80 | bit << 1);
+            mbc.write_ram(eeprom, 0xA080, 0xC0 | bit << 1);
+        }
+    }
+
+    fn send_word(mbc: &mut Mbc7, eeprom: &mut [u8], word: u16, bits: usize) {
+        let bits: Vec<u8> = (0..bits).rev().map(|bit| (word >> bit) as u8 & 0x01).collect();
+        send_bits(mbc, eeprom, &bits);
+    }
+
+    // Pulls chip select low first, to start from a clean state
+    fn send_command(mbc: &mut Mbc7, eeprom: &mut [u8], command: u16) {
+        mbc.write_ram(eeprom, 0xA080, 0x00);
+        send_word(mbc, eeprom, command, 11);
+    }
+
+    fn read_word(mbc: &mut Mbc7, eeprom: &mut [u8], address: u16) -> u16 {
+        send_command(mbc, eeprom, 0b110_0000_0000 | address);
+        assert_eq!(0x00, mbc.read_ram(eeprom, 0xA080) & 0x01);
+        (0..16).fold(0, |word, _| {
+            send_bits(mbc, eeprom, &[0]);
+            word << 1 | (mbc.read_ram(eeprom, 0xA080) & 0x01) as u16
+        })
+    }
+
+    #[test]
+    fn test_registers_need_both_enables(){
+        let mut eeprom = vec![0xFF; MBC7_EEPROM_SIZE];
+        let mut mbc = Mbc7::new();
+        mbc.write_rom(0x0000, 0x0A);
+
+        assert_eq!(0xFF, mbc.read_ram(&eeprom, 0xA060));
+
+        mbc.write_rom(0x4000, 0x40);
+
+        assert_eq!(0x00, mbc.read_ram(&eeprom, 0xA060));
+        assert_eq!(0xFF, mbc.read_ram(&eeprom, 0xB060));
+
+        mbc.write_ram(&mut eeprom, 0xA080, 0x00);
+
+        assert_eq!(0x01, mbc.read_ram(&eeprom, 0xA080));
+    }
+
+    #[test]
+    fn test_accelerometer_latch(){
+        let mut eeprom = vec![0xFF; MBC7_EEPROM_SIZE];
+        let mut mbc = enabled_mbc();
+        mbc.connect(Peripheral::Tilt { x: 1.0, y: -0.5 }).unwrap();
+
+        mbc.write_ram(&mut eeprom, 0xA010, 0xAA);
+
+        assert_eq!(0x00, mbc.read_ram(&eeprom, 0xA020));
+        assert_eq!(0x80, mbc.read_ram(&eeprom, 0xA030));
+
+        mbc.write_ram(&mut eeprom, 0xA000, 0x55);
+        mbc.write_ram(&mut eeprom, 0xA010, 0xAA);
+        mbc.connect(Peripheral::Tilt { x: 0.0, y: 0.0 }).unwrap();
+
+        assert_eq!(0x40, mbc.read_ram(&eeprom, 0xA020));
+        assert_eq!(0x82, mbc.read_ram(&eeprom, 0xA030));
+        assert_eq!(0x98, mbc.read_ram(&eeprom, 0xA040));
+        assert_eq!(0x81, mbc.read_ram(&eeprom, 0xA050));
+
+        mbc.write_ram(&mut eeprom, 0xA010, 0xAA);
+
+        assert_eq!(0x40, mbc.read_ram(&eeprom, 0xA020));
+    }
+
+    #[test]
+    fn test_eeprom_write_protection(){
+        let mut eeprom = vec![0xFF; MBC7_EEPROM_SIZE];
+        let mut mbc = enabled_mbc();
+
+        send_command(&mut mbc, &mut eeprom, 0b101_0000_0011);
+        send_word(&mut mbc, &mut eeprom, 0x1234, 16);
+
+        assert_eq!(0xFFFF, read_word(&mut mbc, &mut eeprom, 0x03));
+
+        send_command(&mut mbc, &mut eeprom, 0b100_1100_0000);
+        send_command(&mut mbc, &mut eeprom, 0b101_0000_0011);
+        send_word(&mut mbc, &mut eeprom, 0x1234, 16);
+
+        assert_eq!(0x01, mbc.read_ram(&eeprom, 0xA080) & 0x01);
+        assert_eq!([0x34, 0x12], eeprom[0x06..0x08]);
+        assert_eq!(0x1234, read_word(&mut mbc, &mut eeprom, 0x03));
+    }
+
+    #[test]
+    fn test_eeprom_sequential_read(){
+        let mut eeprom: Vec<u8> = (0..MBC7_EEPROM_SIZE).map(|index| index as u8).collect();
+        let mut mbc = enabled_mbc();
+
+        assert_eq!(0x0302, read_word(&mut mbc, &mut eeprom, 0x01));
+
+        let next_word = (0..16).fold(0, |word, _| {
+            send_bits(&mut mbc, &mut eeprom, &[0]);
+            word << 1 | (mbc.read_ram(&eeprom, 0xA080) & 0x01) as u16
+        });
+
+        assert_eq!(0x0504, next_word);
+    }
+
+    #[test]
+    fn test_eeprom_erase_and_write_all(){
+        let mut eeprom = vec![0x00; MBC7_EEPROM_SIZE];
+        let mut mbc = enabled_mbc();
+        send_command(&mut mbc, &mut eeprom, 0b100_1100_0000);
+
+        send_command(&mut mbc, &mut eeprom, 0b111_0000_0001);
+
+        assert_eq!([0x00, 0x00, 0xFF, 0xFF, 0x00], eeprom[..5]);
+
+        send_command(&mut mbc, &mut eeprom, 0b100_0100_0000);
+        send_word(&mut mbc, &mut eeprom, 0xA55A, 16);
+
+        assert!(eeprom.chunks(2).all(|word| word == [0x5A, 0xA5]));
+
+        send_command(&mut mbc, &mut eeprom, 0b100_1000_0000);
+
+        assert!(eeprom.iter().all(|byte| *byte == 0xFF));
+    }
+}
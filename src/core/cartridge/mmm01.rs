@@ -0,0 +1,182 @@
+use crate::core::cartridge::mbc::{Mbc, OPEN_BUS, ram_bank_index, read_rom_bank};
+
+// Written to 0x0000-0x1FFF, locks the game selected by the menu in
+const MAP_BIT: u8 = 0x40;
+
+// The collection mapper. It boots into the menu at the end of the ROM, which picks the banks of a
+// game and which bank bits the game still controls, before locking itself into an MBC1 lookalike.
+// The MBC1 banking mode is not emulated.
+#[derive(Debug)]
+pub(crate) struct Mmm01 {
+    // Some dumps move the menu from the end of the ROM to the start, shifting every other bank by 2
+    menu_first: bool,
+    mapped: bool,
+    ram_enabled: bool,
+    // 9 bits. Once mapped, the game only changes the lower bits not fixed by the menu.
+    rom_bank: u16,
+    rom_bank_fixed: u16,
+    ram_bank: u8,
+    ram_bank_fixed: u8
+}
+
+impl Mmm01 {
+    pub(crate) fn new(menu_first: bool) -> Self {
+        Mmm01 {
+            menu_first,
+            mapped: false,
+            ram_enabled: false,
+            rom_bank: 0,
+            rom_bank_fixed: 0,
+            ram_bank: 0,
+            ram_bank_fixed: 0
+        }
+    }
+
+    fn selectable_rom_bits(&self) -> u16 {
+        if self.mapped { 0x1F & !self.rom_bank_fixed } else { 0x7F }
+    }
+
+    fn selectable_ram_bits(&self) -> u8 {
+        if self.mapped { 0x03 & !self.ram_bank_fixed } else { 0x0F }
+    }
+
+    // Where a bank of the ROM chip is in the dump
+    fn dump_bank(&self, rom: &[u8], bank: usize) -> usize {
+        let banks = rom.len() / 0x4000;
+        if self.menu_first { (bank % banks + 2) % banks } else { bank }
+    }
+}
+
+impl Mbc for Mmm01 {
+    fn read_rom(&self, rom: &[u8], address: u16) -> u8 {
+        let upper_half = (address >= 0x4000) as usize;
+        if !self.mapped {
+            // The last 32KiB, where the menu is
+            let banks = rom.len() / 0x4000;
+            return read_rom_bank(rom, self.dump_bank(rom, banks.saturating_sub(2) + upper_half), address);
+        }
+        let selectable = self.selectable_rom_bits();
+        let bank = match address {
+            0x0000..=0x3FFF => self.rom_bank & !selectable,
+            // Like on MBC1, the game selecting bank 0 gets bank 1
+            _ if self.rom_bank & selectable == 0 => self.rom_bank | 0x01,
+            _ => self.rom_bank
+        };
+        read_rom_bank(rom, self.dump_bank(rom, bank as usize), address)
+    }
+
+    fn write_rom(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => {
+                self.ram_enabled = value & 0x0F == 0x0A;
+                if !self.mapped {
+                    self.ram_bank_fixed = (value >> 4) & 0x03;
+                    self.mapped = value & MAP_BIT != 0;
+                }
+            }
+            0x2000..=0x3FFF => {
+                let selectable = self.selectable_rom_bits();
+                self.rom_bank = (self.rom_bank & !selectable) | (value as u16 & selectable);
+            }
+            0x4000..=0x5FFF => {
+                let selectable = self.selectable_ram_bits();
+                self.ram_bank = (self.ram_bank & !selectable) | (value & selectable);
+                if !self.mapped {
+                    self.rom_bank = (self.rom_bank & 0x7F) | ((value as u16 & 0x30) << 3);
+                }
+            }
+            _ => if !self.mapped {
+                // Fixes bits 1-4 of the ROM bank, shrinking the ROM the game sees
+                self.rom_bank_fixed = ((value as u16 >> 2) & 0x0F) << 1;
+            }
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], address: u16) -> u8 {
+        if !self.ram_enabled {
+            return OPEN_BUS;
+        }
+        ram_bank_index(ram, self.ram_bank as usize, address).map_or(OPEN_BUS, |index| ram[index])
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], address: u16, value: u8) {
+        if !self.ram_enabled {
+            return;
+        }
+        if let Some(index) = ram_bank_index(ram, self.ram_bank as usize, address) {
+            ram[index] = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::cartridge::header::numbered_rom;
+    use crate::core::cartridge::mbc::Mbc;
+    use crate::core::cartridge::mmm01::Mmm01;
+
+    #[test]
+    fn test_boots_into_menu(){
+        let rom = numbered_rom(64);
+        let mut mbc = Mmm01::new(false);
+
+        mbc.write_rom(0x2000, 0x05);
+
+        assert_eq!(62, mbc.read_rom(&rom, 0x0000));
+        assert_eq!(63, mbc.read_rom(&rom, 0x4000));
+    }
+
+    #[test]
+    fn test_locks_game(){
+        let rom = numbered_rom(64);
+        let mut mbc = Mmm01::new(false);
+        // Game of 4 banks, starting at bank 0x18
+        mbc.write_rom(0x2000, 0x18);
+        mbc.write_rom(0x6000, 0x38);
+        mbc.write_rom(0x0000, 0x40);
+
+        assert_eq!(0x18, mbc.read_rom(&rom, 0x0000));
+        assert_eq!(0x19, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x2000, 0x03);
+
+        assert_eq!(0x1B, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x2000, 0x1F);
+        mbc.write_rom(0x6000, 0x00);
+
+        assert_eq!(0x1B, mbc.read_rom(&rom, 0x4000));
+        assert_eq!(0x18, mbc.read_rom(&rom, 0x0000));
+    }
+
+    #[test]
+    fn test_menu_first_dump(){
+        let mut rom = numbered_rom(64);
+        rom.rotate_right(0x8000);
+        let mut mbc = Mmm01::new(true);
+
+        assert_eq!(62, mbc.read_rom(&rom, 0x0000));
+        assert_eq!(63, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x2000, 0x18);
+        mbc.write_rom(0x6000, 0x38);
+        mbc.write_rom(0x0000, 0x40);
+
+        assert_eq!(0x18, mbc.read_rom(&rom, 0x0000));
+        assert_eq!(0x19, mbc.read_rom(&rom, 0x4000));
+    }
+
+    #[test]
+    fn test_ram(){
+        let mut ram = vec![0x00; 0x8000];
+        let mut mbc = Mmm01::new(false);
+        mbc.write_rom(0x4000, 0x02);
+        mbc.write_rom(0x0000, 0x6A);
+
+        mbc.write_rom(0x4000, 0x01);
+        mbc.write_ram(&mut ram, 0xA000, 0x12);
+
+        assert_eq!(0x12, ram[0x6000]);
+        assert_eq!(0x12, mbc.read_ram(&ram, 0xA000));
+    }
+}
@@ -0,0 +1,17 @@
+pub mod base;
+mod camera;
+pub mod header;
+mod huc1;
+pub mod huc3;
+pub mod infrared;
+mod mbc;
+mod mbc1;
+mod mbc2;
+mod mbc3;
+pub mod mbc5;
+mod mbc7;
+mod mmm01;
+mod multicart;
+pub mod peripheral;
+pub mod rtc;
+pub mod sensor;
@@ -0,0 +1,173 @@
+use crate::core::cartridge::header::{CARTRIDGE_TYPE_ADDRESS, Header, LOGO_ADDRESS, Mapper, NINTENDO_LOGO};
+use crate::core::cartridge::mbc::{Mbc, OPEN_BUS, ram_bank_index, read_rom_bank};
+
+const GAME_SIZE: usize = 0x8000;
+// Found in the menu of every Wisdom Tree game, whose header claims a plain 32KiB ROM
+const WISDOM_TREE_SIGNATURES: [&[u8]; 2] = [b"WISDOM TREE", b"WISDOM\0TREE"];
+// The Mani 4 in 1 collections, whose header claims an MBC3
+const M161_TITLE: &str = "TETRIS SET";
+
+// MMM01 collections boot into the menu in the last 32KiB, whose header is the one that tells
+// the mapper. The header at the start of the ROM belongs to the first game, unless the dump
+// moved the menu there.
+pub(crate) fn mmm01_menu_offset(rom: &[u8]) -> Option<usize> {
+    let is_mmm01 = |code: u8| (0x0B..=0x0D).contains(&code);
+    if rom.len() <= GAME_SIZE || !rom.len().is_multiple_of(GAME_SIZE) || is_mmm01(rom[CARTRIDGE_TYPE_ADDRESS]) {
+        return None;
+    }
+    let offset = rom.len() - GAME_SIZE;
+    let logo = offset + LOGO_ADDRESS;
+    (is_mmm01(rom[offset + CARTRIDGE_TYPE_ADDRESS]) && rom[logo..logo + NINTENDO_LOGO.len()] == NINTENDO_LOGO)
+        .then_some(offset)
+}
+
+// Unlicensed multicarts whose header names another mapper, recognized by their contents
+pub(crate) fn detect_unlicensed_mapper(rom: &[u8], header: &Header) -> Option<Mapper> {
+    if header.cartridge_type.code == 0x10 && header.title == M161_TITLE && rom.len() == 8 * GAME_SIZE {
+        return Some(Mapper::M161);
+    }
+    let signed = WISDOM_TREE_SIGNATURES.iter()
+        .any(|signature| rom.windows(signature.len()).any(|window| window == *signature));
+    (header.cartridge_type.mapper == Mapper::None && rom.len() > GAME_SIZE && signed).then_some(Mapper::WisdomTree)
+}
+
+fn read_game(rom: &[u8], game: usize, address: u16) -> u8 {
+    read_rom_bank(rom, 2 * game + (address >= 0x4000) as usize, address)
+}
+
+// Switches whole 32KiB games, with the lower byte of the address written to 0x0000-0x3FFF
+#[derive(Debug)]
+pub(crate) struct WisdomTree {
+    game: u8
+}
+
+impl WisdomTree {
+    pub(crate) fn new() -> Self {
+        WisdomTree { game: 0 }
+    }
+}
+
+impl Mbc for WisdomTree {
+    fn read_rom(&self, rom: &[u8], address: u16) -> u8 {
+        read_game(rom, self.game as usize, address)
+    }
+
+    fn write_rom(&mut self, address: u16, _value: u8) {
+        if address < 0x4000 {
+            self.game = address as u8;
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], address: u16) -> u8 {
+        ram_bank_index(ram, 0, address).map_or(OPEN_BUS, |index| ram[index])
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], address: u16, value: u8) {
+        if let Some(index) = ram_bank_index(ram, 0, address) {
+            ram[index] = value;
+        }
+    }
+}
+
+// Switches whole 32KiB games with the first write anywhere in 0x0000-0x7FFF, then ignores
+// any other until the next reset
+#[derive(Debug)]
+pub(crate) struct M161 {
+    game: u8,
+    locked: bool
+}
+
+impl M161 {
+    pub(crate) fn new() -> Self {
+        M161 { game: 0, locked: false }
+    }
+}
+
+impl Mbc for M161 {
+    fn read_rom(&self, rom: &[u8], address: u16) -> u8 {
+        read_game(rom, self.game as usize, address)
+    }
+
+    fn write_rom(&mut self, _address: u16, value: u8) {
+        if !self.locked {
+            self.game = value & 0x07;
+            self.locked = true;
+        }
+    }
+
+    fn read_ram(&self, _ram: &[u8], _address: u16) -> u8 {
+        OPEN_BUS
+    }
+
+    fn write_ram(&mut self, _ram: &mut [u8], _address: u16, _value: u8) {}
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::cartridge::header::{fix_checksums, Header, Mapper, NINTENDO_LOGO, test_rom};
+    use crate::core::cartridge::mbc::Mbc;
+    use crate::core::cartridge::multicart::{detect_unlicensed_mapper, M161, mmm01_menu_offset, WisdomTree};
+
+    #[test]
+    fn test_wisdom_tree(){
+        let mut rom = vec![0x00; 8 * 0x8000];
+        rom[5 * 0x8000] = 0x05;
+        rom[5 * 0x8000 + 0x4000] = 0x15;
+        let mut mbc = WisdomTree::new();
+
+        mbc.write_rom(0x0105, 0x00);
+
+        assert_eq!(0x05, mbc.read_rom(&rom, 0x0000));
+        assert_eq!(0x15, mbc.read_rom(&rom, 0x4000));
+
+        mbc.write_rom(0x4000, 0x00);
+
+        assert_eq!(0x05, mbc.read_rom(&rom, 0x0000));
+    }
+
+    #[test]
+    fn test_m161(){
+        let mut rom = vec![0x00; 8 * 0x8000];
+        rom[3 * 0x8000 + 0x4000] = 0x34;
+        let mut mbc = M161::new();
+
+        mbc.write_rom(0x4000, 0x03);
+        mbc.write_rom(0x4000, 0x01);
+
+        assert_eq!(0x34, mbc.read_rom(&rom, 0x4000));
+    }
+
+    #[test]
+    fn test_detect_unlicensed_mapper(){
+        let mut rom = test_rom(0x00, 0x02, 0x00);
+        let header = Header::parse(&rom).unwrap();
+
+        assert_eq!(None, detect_unlicensed_mapper(&rom, &header));
+
+        rom[0x4000..0x400B].copy_from_slice(b"WISDOM TREE");
+
+        assert_eq!(Some(Mapper::WisdomTree), detect_unlicensed_mapper(&rom, &header));
+
+        let mut rom = test_rom(0x10, 0x03, 0x00);
+        rom[0x0134..0x013E].copy_from_slice(b"TETRIS SET");
+        fix_checksums(&mut rom);
+
+        assert_eq!(Some(Mapper::M161), detect_unlicensed_mapper(&rom, &Header::parse(&rom).unwrap()));
+    }
+
+    #[test]
+    fn test_mmm01_menu_offset(){
+        let mut rom = test_rom(0x01, 0x03, 0x00);
+
+        assert_eq!(None, mmm01_menu_offset(&rom));
+
+        rom[0x38104..0x38134].copy_from_slice(&NINTENDO_LOGO);
+        rom[0x38147] = 0x0B;
+
+        assert_eq!(Some(0x38000), mmm01_menu_offset(&rom));
+
+        rom[0x0147] = 0x0B;
+
+        assert_eq!(None, mmm01_menu_offset(&rom));
+    }
+}
@@ -0,0 +1,46 @@
+use std::fmt::{Debug, Formatter};
+use crate::core::cartridge::huc3::SpeakerCallback;
+use crate::core::cartridge::infrared::IrTransceiver;
+use crate::core::cartridge::mbc5::RumbleCallback;
+use crate::core::cartridge::rtc::TimeSource;
+use crate::core::cartridge::sensor::SensorFrame;
+
+// What the host connects to the extra hardware some cartridges carry. Only the clock is connected
+// outside of the tests for now.
+#[allow(dead_code)]
+pub(crate) enum Peripheral {
+    // Drives the clock of MBC3 and HuC3 cartridges
+    Clock(Box<dyn TimeSource>),
+    // Follows the motor of MBC5 rumble cartridges
+    Rumble(RumbleCallback),
+    // Feeds the accelerometer of MBC7 cartridges, in g along each axis. Positive values tilt
+    // the console right and towards the player.
+    Tilt { x: f32, y: f32 },
+    // Follows the tone generator of HuC3 cartridges
+    Speaker(SpeakerCallback),
+    // Faces the infrared port of HuC1 and HuC3 cartridges
+    Infrared(Box<dyn IrTransceiver>),
+    // What the Game Boy Camera sensor sees, until another frame is connected
+    CameraFrame(SensorFrame)
+}
+
+impl Peripheral {
+    // The hardware of the cartridge it connects to
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Peripheral::Clock(_) => "clock",
+            Peripheral::Rumble(_) => "rumble motor",
+            Peripheral::Tilt { .. } => "accelerometer",
+            Peripheral::Speaker(_) => "speaker",
+            Peripheral::Infrared(_) => "infrared port",
+            Peripheral::CameraFrame(_) => "camera sensor"
+        }
+    }
+}
+
+// The callbacks can not be printed, so only the kind is
+impl Debug for Peripheral {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Peripheral({})", self.name())
+    }
+}
@@ -0,0 +1,387 @@
+use std::fmt::Debug;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The cartridge is clocked at the rate M-cycles run at in double speed
+pub(crate) const CYCLES_PER_SECOND: u64 = 1 << 21;
+// 5 live registers, 5 latched ones, each as a 32 bit word, then a 64 bit UNIX timestamp
+pub(crate) const RTC_FOOTER_SIZE: usize = 48;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const DAYS: u64 = 512;
+
+const HALT_BIT: u8 = 0x40;
+const CARRY_BIT: u8 = 0x80;
+
+// Tells the clocks in a cartridge how much time went by
+pub(crate) trait TimeSource: Debug {
+    // Whole seconds elapsed since the last call, given the cycles emulated in the meantime
+    fn elapsed_seconds(&mut self, cycles: u64) -> u64;
+    // Restarts counting the current second from zero
+    fn reset_subsecond(&mut self);
+    // Used to catch up with the time spent while the emulator was not running
+    fn unix_time(&self) -> u64;
+}
+
+// Counts time by emulated cycles, so the same inputs always give the same clock
+#[derive(Debug)]
+pub(crate) struct CycleTimeSource {
+    unix_time: u64,
+    cycles: u64
+}
+
+impl CycleTimeSource {
+    pub(crate) fn new(unix_time: u64) -> Self {
+        CycleTimeSource { unix_time, cycles: 0 }
+    }
+}
+
+impl TimeSource for CycleTimeSource {
+    fn elapsed_seconds(&mut self, cycles: u64) -> u64 {
+        self.cycles += cycles;
+        let seconds = self.cycles / CYCLES_PER_SECOND;
+        self.cycles %= CYCLES_PER_SECOND;
+        self.unix_time += seconds;
+        seconds
+    }
+
+    fn reset_subsecond(&mut self) {
+        self.cycles = 0;
+    }
+
+    fn unix_time(&self) -> u64 {
+        self.unix_time
+    }
+}
+
+// Follows the clock of the host, like the cartridge does while the console is off
+#[derive(Debug)]
+pub(crate) struct HostTimeSource {
+    last_second: u64
+}
+
+impl HostTimeSource {
+    pub(crate) fn new() -> Self {
+        HostTimeSource { last_second: host_unix_time() }
+    }
+}
+
+impl TimeSource for HostTimeSource {
+    fn elapsed_seconds(&mut self, _cycles: u64) -> u64 {
+        let now = host_unix_time();
+        let seconds = now.saturating_sub(self.last_second);
+        self.last_second = now;
+        seconds
+    }
+
+    // The host clock only has a resolution of a second here
+    fn reset_subsecond(&mut self) {
+        self.last_second = host_unix_time();
+    }
+
+    fn unix_time(&self) -> u64 {
+        host_unix_time()
+    }
+}
+
+fn host_unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs())
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct RtcRegisters {
+    pub(crate) seconds: u8,
+    pub(crate) minutes: u8,
+    pub(crate) hours: u8,
+    // Lower 8 bits of the day counter
+    pub(crate) days_low: u8,
+    // Bit 0 is the 9th bit of the day counter, bit 6 halts the clock, bit 7 is the day carry
+    pub(crate) days_high: u8
+}
+
+impl RtcRegisters {
+    // Registers 0x08-0x0C, as selected through the RAM bank number
+    pub(crate) fn read(&self, register: u8) -> u8 {
+        match register {
+            0x08 => self.seconds & 0x3F,
+            0x09 => self.minutes & 0x3F,
+            0x0A => self.hours & 0x1F,
+            0x0B => self.days_low,
+            _ => self.days_high & (CARRY_BIT | HALT_BIT | 0x01)
+        }
+    }
+
+    fn days(&self) -> u64 {
+        ((self.days_high as u64 & 0x01) << 8) | self.days_low as u64
+    }
+
+    fn set_days(&mut self, days: u64) {
+        self.days_low = days as u8;
+        self.days_high = (self.days_high & !0x01) | ((days >> 8) as u8 & 0x01);
+    }
+
+    fn is_valid(&self) -> bool {
+        self.seconds < 60 && self.minutes < 60 && self.hours < 24
+    }
+
+    // Counters set out of range only wrap at their bit width, without carrying
+    fn advance_second(&mut self) {
+        self.seconds = self.seconds.wrapping_add(1) & 0x3F;
+        if self.seconds != 60 {
+            return;
+        }
+        self.seconds = 0;
+        self.minutes = self.minutes.wrapping_add(1) & 0x3F;
+        if self.minutes != 60 {
+            return;
+        }
+        self.minutes = 0;
+        self.hours = self.hours.wrapping_add(1) & 0x1F;
+        if self.hours != 24 {
+            return;
+        }
+        self.hours = 0;
+        self.advance_days(1);
+    }
+
+    fn advance_days(&mut self, days: u64) {
+        let days = self.days() + days;
+        if days >= DAYS {
+            self.days_high |= CARRY_BIT;
+        }
+        self.set_days(days % DAYS);
+    }
+
+    fn advance(&mut self, mut seconds: u64) {
+        while seconds > 0 && !self.is_valid() {
+            self.advance_second();
+            seconds -= 1;
+        }
+        if seconds == 0 {
+            return;
+        }
+        let time_of_day = self.hours as u64 * 3600 + self.minutes as u64 * 60 + self.seconds as u64 + seconds;
+        self.seconds = (time_of_day % 60) as u8;
+        self.minutes = (time_of_day / 60 % 60) as u8;
+        self.hours = (time_of_day / 3600 % 24) as u8;
+        self.advance_days(time_of_day / SECONDS_PER_DAY);
+    }
+}
+
+// The clock found on MBC3 cartridges
+#[derive(Debug)]
+pub(crate) struct Rtc {
+    pub(crate) live: RtcRegisters,
+    // Copy of the live registers the game reads from
+    pub(crate) latched: RtcRegisters,
+    time_source: Box<dyn TimeSource>
+}
+
+impl Rtc {
+    pub(crate) fn new(time_source: Box<dyn TimeSource>) -> Self {
+        Rtc {
+            live: RtcRegisters::default(),
+            latched: RtcRegisters::default(),
+            time_source
+        }
+    }
+
+    pub(crate) fn set_time_source(&mut self, time_source: Box<dyn TimeSource>) {
+        self.time_source = time_source;
+    }
+
+    pub(crate) fn tick(&mut self, cycles: u64) {
+        let seconds = self.time_source.elapsed_seconds(cycles);
+        if self.live.days_high & HALT_BIT == 0 {
+            self.live.advance(seconds);
+        }
+    }
+
+    pub(crate) fn latch(&mut self) {
+        self.latched = self.live;
+    }
+
+    pub(crate) fn read(&self, register: u8) -> u8 {
+        self.latched.read(register)
+    }
+
+    // Writes go to the live registers, leaving the latched copy untouched
+    pub(crate) fn write(&mut self, register: u8, value: u8) {
+        match register {
+            0x08 => {
+                self.live.seconds = value & 0x3F;
+                // Writing the seconds resets the sub-second divider
+                self.time_source.reset_subsecond();
+            }
+            0x09 => self.live.minutes = value & 0x3F,
+            0x0A => self.live.hours = value & 0x1F,
+            0x0B => self.live.days_low = value,
+            _ => self.live.days_high = value & (CARRY_BIT | HALT_BIT | 0x01)
+        }
+    }
+
+    // Same layout as the footer BGB and VBA-M append to the save
+    pub(crate) fn save(&self) -> Vec<u8> {
+        let mut footer = Vec::with_capacity(RTC_FOOTER_SIZE);
+        for registers in [self.live, self.latched] {
+            for register in 0x08..=0x0C {
+                footer.extend_from_slice(&(registers.read(register) as u32).to_le_bytes());
+            }
+        }
+        footer.extend_from_slice(&self.time_source.unix_time().to_le_bytes());
+        footer
+    }
+
+    // Also accepts the older 44 byte footer, with a 32 bit timestamp
+    pub(crate) fn load(&mut self, footer: &[u8]) {
+        if footer.len() < RTC_FOOTER_SIZE - 4 {
+            return;
+        }
+        let word = |index: usize| footer[index * 4];
+        // Masked like the writes, as the footer may come from another emulator or be corrupted
+        let registers = |first: usize| RtcRegisters {
+            seconds: word(first) & 0x3F,
+            minutes: word(first + 1) & 0x3F,
+            hours: word(first + 2) & 0x1F,
+            days_low: word(first + 3),
+            days_high: word(first + 4) & (CARRY_BIT | HALT_BIT | 0x01)
+        };
+        self.live = registers(0);
+        self.latched = registers(5);
+        let timestamp = if footer.len() >= RTC_FOOTER_SIZE {
+            u64::from_le_bytes(footer[40..48].try_into().unwrap())
+        } else {
+            u32::from_le_bytes(footer[40..44].try_into().unwrap()) as u64
+        };
+        if self.live.days_high & HALT_BIT == 0 {
+            self.live.advance(self.time_source.unix_time().saturating_sub(timestamp));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::cartridge::rtc::{CYCLES_PER_SECOND, CycleTimeSource, Rtc, RtcRegisters, TimeSource};
+
+    fn rtc_registers(seconds: u8, minutes: u8, hours: u8, days_low: u8, days_high: u8) -> RtcRegisters {
+        RtcRegisters { seconds, minutes, hours, days_low, days_high }
+    }
+
+    #[test]
+    fn test_cycle_time_source(){
+        let mut time_source = CycleTimeSource::new(100);
+
+        assert_eq!(0, time_source.elapsed_seconds(CYCLES_PER_SECOND - 1));
+        assert_eq!(1, time_source.elapsed_seconds(1));
+        assert_eq!(2, time_source.elapsed_seconds(2 * CYCLES_PER_SECOND + 5));
+        assert_eq!(103, time_source.unix_time());
+    }
+
+    #[test]
+    fn test_advance(){
+        let mut registers = rtc_registers(59, 59, 23, 0xFF, 0x00);
+
+        registers.advance(1);
+
+        assert_eq!(rtc_registers(0, 0, 0, 0x00, 0x01), registers);
+
+        registers.advance(511 * 86400 + 3661);
+
+        assert_eq!(rtc_registers(1, 1, 1, 0xFF, 0x80), registers);
+    }
+
+    #[test]
+    fn test_advance_out_of_range(){
+        let mut registers = rtc_registers(63, 0, 0, 0, 0);
+
+        registers.advance(1);
+
+        assert_eq!(0, registers.seconds);
+        assert_eq!(0, registers.minutes);
+
+        let mut registers = rtc_registers(59, 59, 31, 0, 0);
+
+        registers.advance(1);
+
+        assert_eq!(0, registers.hours);
+        assert_eq!(0, registers.days_low);
+    }
+
+    #[test]
+    fn test_latch(){
+        let mut rtc = Rtc::new(Box::new(CycleTimeSource::new(0)));
+
+        rtc.tick(3 * CYCLES_PER_SECOND);
+
+        assert_eq!(0, rtc.read(0x08));
+
+        rtc.latch();
+        rtc.tick(CYCLES_PER_SECOND);
+
+        assert_eq!(3, rtc.read(0x08));
+    }
+
+    #[test]
+    fn test_halt(){
+        let mut rtc = Rtc::new(Box::new(CycleTimeSource::new(0)));
+        rtc.write(0x0C, 0x40);
+
+        rtc.tick(CYCLES_PER_SECOND);
+
+        assert_eq!(0, rtc.live.seconds);
+
+        rtc.write(0x0C, 0x00);
+        rtc.tick(CYCLES_PER_SECOND);
+
+        assert_eq!(1, rtc.live.seconds);
+    }
+
+    #[test]
+    fn test_writing_seconds_resets_divider(){
+        let mut rtc = Rtc::new(Box::new(CycleTimeSource::new(0)));
+        rtc.tick(CYCLES_PER_SECOND - 1);
+
+        rtc.write(0x08, 10);
+        rtc.tick(1);
+
+        assert_eq!(10, rtc.live.seconds);
+    }
+
+    #[test]
+    fn test_save_and_load(){
+        let mut rtc = Rtc::new(Box::new(CycleTimeSource::new(1000)));
+        rtc.write(0x08, 30);
+        rtc.write(0x0B, 0x12);
+        rtc.latch();
+
+        let footer = rtc.save();
+
+        assert_eq!(48, footer.len());
+        assert_eq!([30, 0, 0, 0], footer[0..4]);
+        assert_eq!([0x12, 0, 0, 0], footer[12..16]);
+        assert_eq!([30, 0, 0, 0], footer[20..24]);
+        assert_eq!(1000u64.to_le_bytes(), footer[40..48]);
+
+        let mut loaded = Rtc::new(Box::new(CycleTimeSource::new(1090)));
+        loaded.load(&footer);
+
+        assert_eq!(rtc_registers(0, 2, 0, 0x12, 0x00), loaded.live);
+        assert_eq!(rtc_registers(30, 0, 0, 0x12, 0x00), loaded.latched);
+    }
+
+    #[test]
+    fn test_load_masks_registers(){
+        let mut footer = vec![0xFF; 40];
+        footer.extend_from_slice(&1000u64.to_le_bytes());
+        let mut rtc = Rtc::new(Box::new(CycleTimeSource::new(1001)));
+
+        rtc.load(&footer);
+
+        assert_eq!(rtc_registers(0x3F, 0x3F, 0x1F, 0xFF, 0xC1), rtc.latched);
+        assert_eq!(0xC1, rtc.live.days_high);
+
+        rtc.write(0x0C, 0x00);
+        rtc.tick(CYCLES_PER_SECOND);
+
+        assert_eq!(0x00, rtc.live.seconds);
+    }
+}
@@ -0,0 +1,190 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug)]
+pub(crate) enum FrameError {
+    Io(io::Error),
+    // The file is not a PGM or PPM image
+    UnsupportedFormat,
+    Malformed,
+    InvalidSize { width: usize, height: usize, len: usize }
+}
+
+impl Display for FrameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameError::Io(error) => write!(f, "could not read the image: {}", error),
+            FrameError::UnsupportedFormat => write!(f, "the image is not a PGM or PPM file"),
+            FrameError::Malformed => write!(f, "the image is truncated or malformed"),
+            FrameError::InvalidSize { width, height, len } =>
+                write!(f, "{} pixels do not make a {}x{} frame", len, width, height)
+        }
+    }
+}
+
+impl Error for FrameError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FrameError::Io(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for FrameError {
+    fn from(error: io::Error) -> Self {
+        FrameError::Io(error)
+    }
+}
+
+// What the camera sensor sees, as 8 bit grayscale pixels row by row, from black at 0 to white at
+// 255. Frames of any size are scaled to the sensor when captured.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SensorFrame {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>
+}
+
+impl SensorFrame {
+    // Frames only come from the tests until a frontend feeds the camera
+    #[allow(dead_code)]
+    pub(crate) fn new(width: usize, height: usize, pixels: Vec<u8>) -> Result<Self, FrameError> {
+        if width == 0 || height == 0 || width.checked_mul(height) != Some(pixels.len()) {
+            return Err(FrameError::InvalidSize { width, height, len: pixels.len() });
+        }
+        Ok(SensorFrame { width, height, pixels })
+    }
+
+    // A frame of a single shade
+    pub(crate) fn uniform(value: u8) -> Self {
+        SensorFrame { width: 1, height: 1, pixels: vec![value] }
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, FrameError> {
+        SensorFrame::parse_pnm(&fs::read(path)?)
+    }
+
+    // Reads binary (P5, P6) and plain (P2, P3) PGM and PPM images, turning colors into luma
+    pub(crate) fn parse_pnm(data: &[u8]) -> Result<Self, FrameError> {
+        let (channels, plain) = match data.get(0..2) {
+            Some(b"P2") => (1, true),
+            Some(b"P3") => (3, true),
+            Some(b"P5") => (1, false),
+            Some(b"P6") => (3, false),
+            _ => return Err(FrameError::UnsupportedFormat)
+        };
+        let mut position = 2;
+        let mut header = [0usize; 3];
+        for value in &mut header {
+            *value = next_number(data, &mut position).ok_or(FrameError::Malformed)?;
+        }
+        let [width, height, max_value] = header;
+        if max_value == 0 || max_value > 0xFFFF {
+            return Err(FrameError::Malformed);
+        }
+        let samples = width.checked_mul(height).and_then(|pixels| pixels.checked_mul(channels))
+            .ok_or(FrameError::Malformed)?;
+        let samples: Vec<usize> = if plain {
+            (0..samples)
+                .map(|_| next_number(data, &mut position).filter(|sample| *sample <= max_value))
+                .collect::<Option<_>>()
+                .ok_or(FrameError::Malformed)?
+        } else {
+            // A single whitespace character separates the header from the samples
+            let bytes_per_sample = if max_value < 0x100 { 1 } else { 2 };
+            let raster = samples.checked_mul(bytes_per_sample)
+                .and_then(|len| len.checked_add(position + 1))
+                .and_then(|end| data.get(position + 1..end))
+                .ok_or(FrameError::Malformed)?;
+            raster.chunks(bytes_per_sample)
+                .map(|sample| sample.iter().fold(0, |value, byte| value << 8 | *byte as usize))
+                .collect()
+        };
+        let pixels = samples.chunks(channels)
+            .map(|pixel| {
+                let luma = match pixel {
+                    [red, green, blue] => (299 * red + 587 * green + 114 * blue) / 1000,
+                    _ => pixel[0]
+                };
+                (luma.min(max_value) * 0xFF / max_value) as u8
+            })
+            .collect();
+        SensorFrame::new(width, height, pixels)
+    }
+
+    // Nearest pixel to the given position on a grid of the given size
+    pub(crate) fn sample(&self, x: usize, y: usize, width: usize, height: usize) -> u8 {
+        self.pixels[(y * self.height / height) * self.width + x * self.width / width]
+    }
+}
+
+// Parses the next decimal number, skipping whitespace and comments
+fn next_number(data: &[u8], position: &mut usize) -> Option<usize> {
+    while let Some(byte) = data.get(*position) {
+        match byte {
+            b'#' => while data.get(*position).is_some_and(|byte| *byte != b'\n') {
+                *position += 1;
+            },
+            byte if byte.is_ascii_whitespace() => *position += 1,
+            _ => break
+        }
+    }
+    let start = *position;
+    while data.get(*position).is_some_and(|byte| byte.is_ascii_digit()) {
+        *position += 1;
+    }
+    std::str::from_utf8(&data[start..*position]).ok()?.parse().ok()
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::cartridge::sensor::{FrameError, SensorFrame};
+
+    #[test]
+    fn test_new(){
+        let frame = SensorFrame::new(2, 2, vec![0x00, 0x40, 0x80, 0xFF]).unwrap();
+
+        assert_eq!(0x00, frame.sample(0, 0, 128, 112));
+        assert_eq!(0x40, frame.sample(64, 55, 128, 112));
+        assert_eq!(0xFF, frame.sample(127, 111, 128, 112));
+        assert!(matches!(SensorFrame::new(2, 2, vec![0x00; 3]), Err(FrameError::InvalidSize { len: 3, .. })));
+        assert!(matches!(SensorFrame::new(usize::MAX, 2, vec![0x00; 3]), Err(FrameError::InvalidSize { .. })));
+    }
+
+    #[test]
+    fn test_parse_pgm(){
+        let mut binary = b"P5\n# comment\n2 1\n255\n".to_vec();
+        binary.extend_from_slice(&[0x12, 0xFF]);
+        let plain = b"P2 2 1 15 0 15";
+
+        assert_eq!(SensorFrame::new(2, 1, vec![0x12, 0xFF]).unwrap(), SensorFrame::parse_pnm(&binary).unwrap());
+        assert_eq!(SensorFrame::new(2, 1, vec![0x00, 0xFF]).unwrap(), SensorFrame::parse_pnm(plain).unwrap());
+    }
+
+    #[test]
+    fn test_parse_ppm(){
+        let mut binary = b"P6 2 1 255\n".to_vec();
+        binary.extend_from_slice(&[0xFF, 0x00, 0x00, 0xFF, 0xFF, 0xFF]);
+        let plain = b"P3 1 1 65535 0 65535 0";
+
+        assert_eq!(SensorFrame::new(2, 1, vec![76, 0xFF]).unwrap(), SensorFrame::parse_pnm(&binary).unwrap());
+        assert_eq!(SensorFrame::new(1, 1, vec![149]).unwrap(), SensorFrame::parse_pnm(plain).unwrap());
+    }
+
+    #[test]
+    fn test_parse_invalid(){
+        assert!(matches!(SensorFrame::parse_pnm(b"BM"), Err(FrameError::UnsupportedFormat)));
+        assert!(matches!(SensorFrame::parse_pnm(b"P5 2 2 255\n\x00"), Err(FrameError::Malformed)));
+        assert!(matches!(SensorFrame::parse_pnm(b"P2 2 1 0 0 0"), Err(FrameError::Malformed)));
+        assert!(matches!(SensorFrame::parse_pnm(b"P5 99999999999 99999999999 255\n"), Err(FrameError::Malformed)));
+        assert!(matches!(SensorFrame::parse_pnm(b"P2 99999999999 99999999999 255 0"), Err(FrameError::Malformed)));
+        assert!(matches!(SensorFrame::parse_pnm(b"P3 1 1 255 100000000000000000 0 0"), Err(FrameError::Malformed)));
+        assert!(matches!(SensorFrame::parse_pnm(b"P2 1 1 255 256"), Err(FrameError::Malformed)));
+        assert_eq!("the image is not a PGM or PPM file", FrameError::UnsupportedFormat.to_string());
+    }
+}
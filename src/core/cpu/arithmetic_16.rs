@@ -0,0 +1,290 @@
+use crate::core::cpu::base::CPU;
+use crate::core::instructions::definitions::RegisterTarget16;
+
+impl CPU {
+    pub(super) fn add_hl_register16(&mut self, source: RegisterTarget16) {
+        let hl_value = self.registers.get_hl();
+        let value = self.get_register_value_16(source);
+        let (new_value, did_overflow) = hl_value.overflowing_add(value);
+
+        self.registers.f.subtract = false;
+        self.registers.f.half_carry = (hl_value & 0xFFF) + (value & 0xFFF) > 0xFFF;
+        self.registers.f.carry = did_overflow;
+        self.tick();
+        self.registers.set_hl(new_value);
+    }
+
+    pub(super) fn increment_register16(&mut self, target: RegisterTarget16) {
+        let value = self.get_register_value_16(target);
+        self.tick();
+        self.set_register_value_16(target, value.wrapping_add(1));
+    }
+
+    pub(super) fn decrement_register16(&mut self, target: RegisterTarget16) {
+        let value = self.get_register_value_16(target);
+        self.tick();
+        self.set_register_value_16(target, value.wrapping_sub(1));
+    }
+
+    pub(super) fn add_stack_pointer_e(&mut self) {
+        let new_value = self.stack_pointer_plus_e();
+        self.tick();
+        self.tick();
+        self.stack_pointer = new_value;
+    }
+
+    pub(super) fn load_hl_stack_pointer_e(&mut self) {
+        let new_value = self.stack_pointer_plus_e();
+        self.tick();
+        self.registers.set_hl(new_value);
+    }
+
+    // Reads the signed offset E (from pc) and adds it to SP. Even though the offset is signed,
+    // H and C are computed as an unsigned addition of E to the low byte of SP.
+    fn stack_pointer_plus_e(&mut self) -> u16 {
+        let e = self.read_byte_and_increment_pc();
+        let stack_pointer = self.stack_pointer;
+
+        self.registers.f.zero = false;
+        self.registers.f.subtract = false;
+        self.registers.f.half_carry = (stack_pointer & 0xF) + (e as u16 & 0xF) > 0xF;
+        self.registers.f.carry = (stack_pointer & 0xFF) + e as u16 > 0xFF;
+
+        stack_pointer.wrapping_add_signed(e as i8 as i16)
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use strum::IntoEnumIterator;
+    use crate::core::cpu::base::CPU;
+    use crate::core::instructions::definitions::RegisterTarget16;
+    use crate::core::model::Model;
+    use crate::core::registers::FlagRegister;
+    use crate::util::Randomizable;
+
+    #[test]
+    fn test_add_hl_register16(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.set_hl(0x8A23);
+        cpu.registers.set_bc(0x0605);
+
+        cpu.add_hl_register16(RegisterTarget16::BC);
+
+        assert_eq!(0x9028, cpu.registers.get_hl());
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: true,
+            carry: false
+        }, cpu.registers.f);
+
+        cpu.add_hl_register16(RegisterTarget16::HL);
+
+        assert_eq!(0x2050, cpu.registers.get_hl());
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_add_hl_register16_keeps_zero(){
+        for zero in [false, true] {
+            let mut cpu = CPU::new(Model::Dmg);
+            cpu.registers.f.zero = zero;
+            cpu.registers.f.subtract = true;
+            cpu.registers.set_hl(0xFFFF);
+            cpu.stack_pointer = 0x1;
+
+            cpu.add_hl_register16(RegisterTarget16::SP);
+
+            assert_eq!(0x0, cpu.registers.get_hl());
+            assert_eq!(FlagRegister{
+                zero,
+                subtract: false,
+                half_carry: true,
+                carry: true
+            }, cpu.registers.f);
+        }
+    }
+
+    #[test]
+    fn test_add_hl_register16_every_register(){
+        for source in RegisterTarget16::iter() {
+            let mut cpu = CPU::new(Model::Dmg);
+            let hl_value = 0x0100;
+            let value = if source == RegisterTarget16::HL { hl_value } else { u16::random() };
+            cpu.registers.set_hl(hl_value);
+            cpu.set_register_value_16(source, value);
+
+            cpu.add_hl_register16(source);
+
+            assert_eq!(hl_value.wrapping_add(value), cpu.registers.get_hl());
+        }
+    }
+
+    #[test]
+    fn test_increment_register16(){
+        for target in RegisterTarget16::iter() {
+            let mut cpu = CPU::new(Model::Dmg);
+            let value = u16::random();
+            cpu.registers.f = FlagRegister::from(0xF0);
+            cpu.set_register_value_16(target, value);
+
+            cpu.increment_register16(target);
+
+            assert_eq!(value.wrapping_add(1), cpu.get_register_value_16(target));
+            assert_eq!(FlagRegister::from(0xF0), cpu.registers.f);
+        }
+
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.set_de(0xFFFF);
+
+        cpu.increment_register16(RegisterTarget16::DE);
+
+        assert_eq!(0x0, cpu.registers.get_de());
+        assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+    }
+
+    #[test]
+    fn test_decrement_register16(){
+        for target in RegisterTarget16::iter() {
+            let mut cpu = CPU::new(Model::Dmg);
+            let value = u16::random();
+            cpu.registers.f = FlagRegister::from(0xF0);
+            cpu.set_register_value_16(target, value);
+
+            cpu.decrement_register16(target);
+
+            assert_eq!(value.wrapping_sub(1), cpu.get_register_value_16(target));
+            assert_eq!(FlagRegister::from(0xF0), cpu.registers.f);
+        }
+
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.set_bc(0x0);
+
+        cpu.decrement_register16(RegisterTarget16::BC);
+
+        assert_eq!(0xFFFF, cpu.registers.get_bc());
+        assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+    }
+
+    #[test]
+    fn test_add_stack_pointer_e_positive(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        cpu.bus.write_byte(0xC000, 0x08);
+        cpu.stack_pointer = 0xFFF8;
+        cpu.registers.f.zero = true;
+        cpu.registers.f.subtract = true;
+
+        cpu.add_stack_pointer_e();
+
+        assert_eq!(0x0000, cpu.stack_pointer);
+        assert_eq!(0xC001, cpu.program_counter);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: true,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_add_stack_pointer_e_negative(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        // -1
+        cpu.bus.write_byte(0xC000, 0xFF);
+        cpu.stack_pointer = 0x1000;
+
+        cpu.add_stack_pointer_e();
+
+        assert_eq!(0x0FFF, cpu.stack_pointer);
+        // flags come from the unsigned addition 0x00 + 0xFF on the low byte
+        assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+
+        cpu.program_counter = 0xC000;
+        cpu.stack_pointer = 0x1001;
+
+        cpu.add_stack_pointer_e();
+
+        assert_eq!(0x1000, cpu.stack_pointer);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: true,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_add_stack_pointer_e_never_sets_zero(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        cpu.bus.write_byte(0xC000, 0x0);
+        cpu.stack_pointer = 0x0;
+
+        cpu.add_stack_pointer_e();
+
+        assert_eq!(0x0, cpu.stack_pointer);
+        assert!(!cpu.registers.f.zero);
+    }
+
+    #[test]
+    fn test_load_hl_stack_pointer_e_positive(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        cpu.bus.write_byte(0xC000, 0x02);
+        cpu.stack_pointer = 0xFFF8;
+
+        cpu.load_hl_stack_pointer_e();
+
+        assert_eq!(0xFFFA, cpu.registers.get_hl());
+        assert_eq!(0xFFF8, cpu.stack_pointer);
+        assert_eq!(0xC001, cpu.program_counter);
+        assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+    }
+
+    #[test]
+    fn test_load_hl_stack_pointer_e_negative(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        // -2
+        cpu.bus.write_byte(0xC000, 0xFE);
+        cpu.stack_pointer = 0x000F;
+        cpu.registers.f.zero = true;
+
+        cpu.load_hl_stack_pointer_e();
+
+        assert_eq!(0x000D, cpu.registers.get_hl());
+        assert_eq!(0x000F, cpu.stack_pointer);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: true,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_load_hl_stack_pointer_e_low_byte_carry(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        cpu.bus.write_byte(0xC000, 0x01);
+        cpu.stack_pointer = 0x00FF;
+
+        cpu.load_hl_stack_pointer_e();
+
+        assert_eq!(0x0100, cpu.registers.get_hl());
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: true,
+            carry: true
+        }, cpu.registers.f);
+    }
+}
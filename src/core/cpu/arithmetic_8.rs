@@ -9,7 +9,7 @@ impl CPU{
     }
     pub (super) fn add_indirect_hl(&mut self) {
         let address = self.registers.get_hl();
-        let value = self.bus.read_byte(address);
+        let value = self.read_byte(address);
         self.add_constant(value);
     }
     pub (super) fn add_n(&mut self) {
@@ -22,13 +22,147 @@ impl CPU{
     }
     pub (super) fn add_carry_indirect_hl(&mut self) {
         let address = self.registers.get_hl();
-        let value = self.bus.read_byte(address);
+        let value = self.read_byte(address);
         self.add_constant_carry(value);
     }
     pub (super) fn add_carry_n(&mut self) {
         let value = self.read_byte_and_increment_pc();
         self.add_constant_carry(value);
     }
+    pub (super) fn sub_register(&mut self, target: RegisterTarget) {
+        let value = self.get_register_value(target);
+        self.sub_constant(value);
+    }
+    pub (super) fn sub_indirect_hl(&mut self) {
+        let value = self.read_indirect_hl();
+        self.sub_constant(value);
+    }
+    pub (super) fn sub_n(&mut self) {
+        let value = self.read_byte_and_increment_pc();
+        self.sub_constant(value);
+    }
+    pub (super) fn sub_carry_register(&mut self, target: RegisterTarget) {
+        let value = self.get_register_value(target);
+        self.sub_constant_carry(value);
+    }
+    pub (super) fn sub_carry_indirect_hl(&mut self) {
+        let value = self.read_indirect_hl();
+        self.sub_constant_carry(value);
+    }
+    pub (super) fn sub_carry_n(&mut self) {
+        let value = self.read_byte_and_increment_pc();
+        self.sub_constant_carry(value);
+    }
+    pub (super) fn and_register(&mut self, target: RegisterTarget) {
+        let value = self.get_register_value(target);
+        self.and_constant(value);
+    }
+    pub (super) fn and_indirect_hl(&mut self) {
+        let value = self.read_indirect_hl();
+        self.and_constant(value);
+    }
+    pub (super) fn and_n(&mut self) {
+        let value = self.read_byte_and_increment_pc();
+        self.and_constant(value);
+    }
+    pub (super) fn or_register(&mut self, target: RegisterTarget) {
+        let value = self.get_register_value(target);
+        self.or_constant(value);
+    }
+    pub (super) fn or_indirect_hl(&mut self) {
+        let value = self.read_indirect_hl();
+        self.or_constant(value);
+    }
+    pub (super) fn or_n(&mut self) {
+        let value = self.read_byte_and_increment_pc();
+        self.or_constant(value);
+    }
+    pub (super) fn xor_register(&mut self, target: RegisterTarget) {
+        let value = self.get_register_value(target);
+        self.xor_constant(value);
+    }
+    pub (super) fn xor_indirect_hl(&mut self) {
+        let value = self.read_indirect_hl();
+        self.xor_constant(value);
+    }
+    pub (super) fn xor_n(&mut self) {
+        let value = self.read_byte_and_increment_pc();
+        self.xor_constant(value);
+    }
+    pub (super) fn compare_register(&mut self, target: RegisterTarget) {
+        let value = self.get_register_value(target);
+        self.compare_constant(value);
+    }
+    pub (super) fn compare_indirect_hl(&mut self) {
+        let value = self.read_indirect_hl();
+        self.compare_constant(value);
+    }
+    pub (super) fn compare_n(&mut self) {
+        let value = self.read_byte_and_increment_pc();
+        self.compare_constant(value);
+    }
+    pub (super) fn increment_register(&mut self, target: RegisterTarget) {
+        let value = self.get_register_value(target);
+        let new_value = self.increment_constant(value);
+        self.set_register_value(target, new_value);
+    }
+    pub (super) fn increment_indirect_hl(&mut self) {
+        let address = self.registers.get_hl();
+        let value = self.read_byte(address);
+        let new_value = self.increment_constant(value);
+        self.write_byte(address, new_value);
+    }
+    pub (super) fn decrement_register(&mut self, target: RegisterTarget) {
+        let value = self.get_register_value(target);
+        let new_value = self.decrement_constant(value);
+        self.set_register_value(target, new_value);
+    }
+    pub (super) fn decrement_indirect_hl(&mut self) {
+        let address = self.registers.get_hl();
+        let value = self.read_byte(address);
+        let new_value = self.decrement_constant(value);
+        self.write_byte(address, new_value);
+    }
+    pub (super) fn decimal_adjust_a(&mut self) {
+        let mut a = self.registers.a;
+        let mut carry = self.registers.f.carry;
+        if self.registers.f.subtract {
+            // after a subtraction, only the flags tell which digits borrowed
+            if carry {
+                a = a.wrapping_sub(0x60);
+            }
+            if self.registers.f.half_carry {
+                a = a.wrapping_sub(0x06);
+            }
+        } else {
+            if carry || a > 0x99 {
+                a = a.wrapping_add(0x60);
+                carry = true;
+            }
+            if self.registers.f.half_carry || (a & 0xF) > 0x9 {
+                a = a.wrapping_add(0x06);
+            }
+        }
+        self.registers.f.zero = a == 0;
+        self.registers.f.half_carry = false;
+        self.registers.f.carry = carry;
+        self.registers.a = a;
+    }
+    pub (super) fn complement_a(&mut self) {
+        self.registers.a = !self.registers.a;
+        self.registers.f.subtract = true;
+        self.registers.f.half_carry = true;
+    }
+    pub (super) fn set_carry_flag(&mut self) {
+        self.registers.f.subtract = false;
+        self.registers.f.half_carry = false;
+        self.registers.f.carry = true;
+    }
+    pub (super) fn complement_carry_flag(&mut self) {
+        self.registers.f.subtract = false;
+        self.registers.f.half_carry = false;
+        self.registers.f.carry = !self.registers.f.carry;
+    }
 
     //////////////////////////////////////////////////////
 
@@ -42,16 +176,17 @@ impl CPU{
         self.registers.a = new_value;
     }
     fn add_constant_carry(&mut self, value:u8) {
+        let carry = self.registers.f.carry as u8;
         let (mut new_value, mut did_overflow) = self.registers.a.overflowing_add(value);
 
         let carry_did_overflow;
-        (new_value, carry_did_overflow) = new_value.overflowing_add(self.registers.f.carry as u8);
+        (new_value, carry_did_overflow) = new_value.overflowing_add(carry);
         did_overflow = did_overflow || carry_did_overflow;
 
         self.registers.f.zero = new_value == 0;
         self.registers.f.subtract = false;
         self.registers.f.carry = did_overflow;
-        self.registers.f.half_carry = ((self.registers.a & 0xF) + (value & 0xF) + (self.registers.f.carry as u8)) > 0xF;
+        self.registers.f.half_carry = ((self.registers.a & 0xF) + (value & 0xF) + carry) > 0xF;
         self.registers.a = new_value;
     }
 
@@ -64,18 +199,87 @@ impl CPU{
         self.registers.f.half_carry = half_sub > 0xF;
         self.registers.a = new_value;
     }
+
+    fn sub_constant_carry(&mut self, value: u8) {
+        let carry = self.registers.f.carry as u8;
+        let (mut new_value, mut did_overflow) = self.registers.a.overflowing_sub(value);
+
+        let carry_did_overflow;
+        (new_value, carry_did_overflow) = new_value.overflowing_sub(carry);
+        did_overflow = did_overflow || carry_did_overflow;
+
+        self.registers.f.zero = new_value == 0;
+        self.registers.f.subtract = true;
+        self.registers.f.carry = did_overflow;
+        self.registers.f.half_carry = (self.registers.a & 0xF) < (value & 0xF) + carry;
+        self.registers.a = new_value;
+    }
+
+    // INC and DEC leave the carry flag untouched
+    fn increment_constant(&mut self, value: u8) -> u8 {
+        let new_value = value.wrapping_add(1);
+        self.registers.f.zero = new_value == 0;
+        self.registers.f.subtract = false;
+        self.registers.f.half_carry = (value & 0xF) == 0xF;
+        new_value
+    }
+
+    fn decrement_constant(&mut self, value: u8) -> u8 {
+        let new_value = value.wrapping_sub(1);
+        self.registers.f.zero = new_value == 0;
+        self.registers.f.subtract = true;
+        self.registers.f.half_carry = (value & 0xF) == 0x0;
+        new_value
+    }
+
+    fn and_constant(&mut self, value: u8) {
+        let new_value = self.registers.a & value;
+        self.registers.f.zero = new_value == 0;
+        self.registers.f.subtract = false;
+        self.registers.f.half_carry = true;
+        self.registers.f.carry = false;
+        self.registers.a = new_value;
+    }
+
+    fn or_constant(&mut self, value: u8) {
+        let new_value = self.registers.a | value;
+        self.set_logic_result(new_value);
+    }
+
+    fn xor_constant(&mut self, value: u8) {
+        let new_value = self.registers.a ^ value;
+        self.set_logic_result(new_value);
+    }
+
+    fn set_logic_result(&mut self, new_value: u8) {
+        self.registers.f.zero = new_value == 0;
+        self.registers.f.subtract = false;
+        self.registers.f.half_carry = false;
+        self.registers.f.carry = false;
+        self.registers.a = new_value;
+    }
+
+    // CP is a SUB that discards the result, keeping only the flags
+    fn compare_constant(&mut self, value: u8) {
+        let a = self.registers.a;
+        self.sub_constant(value);
+        self.registers.a = a;
+    }
     ///////////////////////////////////////////////////
 }
 
 #[cfg(test)]
 mod test{
+    use strum::IntoEnumIterator;
     use crate::core::cpu::base::CPU;
     use crate::core::instructions::definitions::RegisterTarget;
+    use crate::core::model::Model;
     use crate::core::registers::FlagRegister;
+    use crate::util::Randomizable;
 
     #[test]
     fn test_add_constant(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         cpu.add_constant(1);
 
         assert_eq!(1, cpu.registers.a);
@@ -84,7 +288,7 @@ mod test{
 
     #[test]
     fn test_add_flags(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         cpu.registers.a = 0xFF;
 
         cpu.add_constant(1);
@@ -100,7 +304,7 @@ mod test{
 
     #[test]
     fn test_add_constant_carry_no_carry(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         cpu.registers.a = 1;
         cpu.registers.f.carry = false;
 
@@ -112,7 +316,7 @@ mod test{
 
     #[test]
     fn test_add_constant_carry(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         cpu.registers.f.carry = true;
         cpu.registers.a = 0xFF;
 
@@ -131,7 +335,7 @@ mod test{
 
     #[test]
     fn test_sub_constant(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
 
         cpu.sub_constant(0x1);
 
@@ -147,7 +351,7 @@ mod test{
     // matching instructions
     #[test]
     fn test_add(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         cpu.registers.c = 0x10;
         cpu.registers.h = 0x3;
 
@@ -166,7 +370,7 @@ mod test{
 
     #[test]
     fn test_adc(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         cpu.registers.f.carry = true;
         cpu.registers.e = 0x13;
 
@@ -179,8 +383,8 @@ mod test{
 
     #[test]
     fn test_add_hl(){
-        let mut cpu = CPU::new();
-        let hl_address = 0x1234;
+        let mut cpu = CPU::new(Model::Dmg);
+        let hl_address = 0xC234;
         let value = 0x11;
         let a_value = 0xF0;
         cpu.registers.set_hl(hl_address);
@@ -201,7 +405,7 @@ mod test{
 
     #[test]
     fn test_adc_r(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         cpu.registers.b = 0x2;
         cpu.registers.f.carry = true;
 
@@ -212,8 +416,8 @@ mod test{
     }
     #[test]
     fn test_adc_hl() {
-        let mut cpu = CPU::new();
-        let hl_address = 0x1234;
+        let mut cpu = CPU::new(Model::Dmg);
+        let hl_address = 0xC234;
         let value = 0x11;
         let a_value = 0xF0;
         cpu.registers.set_hl(hl_address);
@@ -233,29 +437,732 @@ mod test{
 
     #[test]
     fn test_adc_n() {
-        let mut cpu = CPU::new();
-        cpu.program_counter = 0x0;
-        cpu.bus.write_byte(0x0, 0x10);
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        cpu.bus.write_byte(0xC000, 0x10);
         cpu.registers.f.carry = true;
 
         cpu.add_carry_n();
 
         assert_eq!(0x11, cpu.registers.a);
         assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
-        assert_eq!(0x1, cpu.program_counter);
+        assert_eq!(0xC001, cpu.program_counter);
     }
 
     #[test]
     fn test_add_n(){
-        let mut cpu = CPU::new();
-        cpu.program_counter = 0x0;
-        cpu.bus.write_byte(0x0, 0x10);
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        cpu.bus.write_byte(0xC000, 0x10);
 
         cpu.add_n();
 
         assert_eq!(0x10, cpu.registers.a);
         assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
-        assert_eq!(0x1, cpu.program_counter);
+        assert_eq!(0xC001, cpu.program_counter);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_adc_half_carry_uses_incoming_carry(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x0E;
+        cpu.registers.f.carry = true;
+
+        cpu.add_constant_carry(0x01);
+
+        assert_eq!(0x10, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: true,
+            carry: false
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_sub_constant_zero(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x3E;
+
+        cpu.sub_constant(0x3E);
+
+        assert_eq!(0x0, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: true,
+            subtract: true,
+            half_carry: false,
+            carry: false
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_sub_constant_carry(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x3B;
+        cpu.registers.f.carry = true;
+
+        cpu.sub_constant_carry(0x2A);
+
+        assert_eq!(0x10, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: true,
+            half_carry: false,
+            carry: false
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_sub_constant_carry_flags(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x3B;
+        cpu.registers.f.carry = true;
+
+        cpu.sub_constant_carry(0x4F);
+
+        assert_eq!(0xEB, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: true,
+            half_carry: true,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_sub_constant_carry_borrow_from_carry_only(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x0;
+        cpu.registers.f.carry = true;
+
+        cpu.sub_constant_carry(0x0);
+
+        assert_eq!(0xFF, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: true,
+            half_carry: true,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_and_constant(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x5A;
+        cpu.registers.f.carry = true;
+
+        cpu.and_constant(0x3F);
+
+        assert_eq!(0x1A, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: true,
+            carry: false
+        }, cpu.registers.f);
+
+        cpu.and_constant(0x0);
+
+        assert_eq!(0x0, cpu.registers.a);
+        assert!(cpu.registers.f.zero);
+    }
+
+    #[test]
+    fn test_or_constant(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x5A;
+        cpu.registers.f = FlagRegister::from(0xF0);
+
+        cpu.or_constant(0x03);
+
+        assert_eq!(0x5B, cpu.registers.a);
+        assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+
+        cpu.registers.a = 0x0;
+
+        cpu.or_constant(0x0);
+
+        assert_eq!(FlagRegister{
+            zero: true,
+            subtract: false,
+            half_carry: false,
+            carry: false
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_xor_constant(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0xFF;
+        cpu.registers.f = FlagRegister::from(0xF0);
+
+        cpu.xor_constant(0x0F);
+
+        assert_eq!(0xF0, cpu.registers.a);
+        assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+
+        cpu.xor_constant(0xF0);
+
+        assert_eq!(0x0, cpu.registers.a);
+        assert!(cpu.registers.f.zero);
+    }
+
+    #[test]
+    fn test_compare_constant(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x3C;
+
+        cpu.compare_constant(0x2F);
+
+        assert_eq!(0x3C, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: true,
+            half_carry: true,
+            carry: false
+        }, cpu.registers.f);
+
+        cpu.compare_constant(0x3C);
+
+        assert_eq!(0x3C, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: true,
+            subtract: true,
+            half_carry: false,
+            carry: false
+        }, cpu.registers.f);
+
+        cpu.compare_constant(0x40);
+
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: true,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_sub_register(){
+        for source in RegisterTarget::iter() {
+            let mut cpu = CPU::new(Model::Dmg);
+            let value = u8::random();
+            cpu.registers.a = 0xFF;
+            cpu.set_register_value(source, value);
+
+            cpu.sub_register(source);
+
+            let expected = if source == RegisterTarget::A { 0x0 } else { 0xFF - value };
+            assert_eq!(expected, cpu.registers.a);
+            assert!(cpu.registers.f.subtract);
+            assert!(!cpu.registers.f.carry);
+        }
+    }
+
+    #[test]
+    fn test_sbc_r(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x10;
+        cpu.registers.d = 0x2;
+        cpu.registers.f.carry = true;
+
+        cpu.sub_carry_register(RegisterTarget::D);
+
+        assert_eq!(0xD, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: true,
+            half_carry: true,
+            carry: false
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_sub_hl(){
+        let mut cpu = CPU::new(Model::Dmg);
+        let hl_address = 0xC234;
+        cpu.registers.set_hl(hl_address);
+        cpu.bus.write_byte(hl_address, 0x11);
+        cpu.registers.a = 0x10;
+
+        cpu.sub_indirect_hl();
+
+        assert_eq!(0xFF, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: true,
+            half_carry: true,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_sbc_hl(){
+        let mut cpu = CPU::new(Model::Dmg);
+        let hl_address = 0xC234;
+        cpu.registers.set_hl(hl_address);
+        cpu.bus.write_byte(hl_address, 0x0F);
+        cpu.registers.a = 0x10;
+        cpu.registers.f.carry = true;
+
+        cpu.sub_carry_indirect_hl();
+
+        assert_eq!(0x0, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: true,
+            subtract: true,
+            half_carry: true,
+            carry: false
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_sub_n(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        cpu.bus.write_byte(0xC000, 0x10);
+        cpu.registers.a = 0x20;
+
+        cpu.sub_n();
+
+        assert_eq!(0x10, cpu.registers.a);
+        assert_eq!(0xC001, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_sbc_n(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        cpu.bus.write_byte(0xC000, 0x10);
+        cpu.registers.a = 0x20;
+        cpu.registers.f.carry = true;
+
+        cpu.sub_carry_n();
+
+        assert_eq!(0x0F, cpu.registers.a);
+        assert_eq!(0xC001, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_and_r(){
+        for source in RegisterTarget::iter() {
+            let mut cpu = CPU::new(Model::Dmg);
+            let value = u8::random();
+            cpu.registers.a = 0xF0;
+            cpu.set_register_value(source, value);
+
+            cpu.and_register(source);
+
+            let expected = if source == RegisterTarget::A { value } else { 0xF0 & value };
+            assert_eq!(expected, cpu.registers.a);
+            assert!(cpu.registers.f.half_carry);
+        }
+    }
+
+    #[test]
+    fn test_and_hl_and_n(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        let hl_address = 0xC234;
+        cpu.registers.set_hl(hl_address);
+        cpu.bus.write_byte(hl_address, 0x0F);
+        cpu.bus.write_byte(0xC000, 0x03);
+        cpu.registers.a = 0x3C;
+
+        cpu.and_indirect_hl();
+
+        assert_eq!(0x0C, cpu.registers.a);
+
+        cpu.and_n();
+
+        assert_eq!(0x0, cpu.registers.a);
+        assert!(cpu.registers.f.zero);
+        assert_eq!(0xC001, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_or_r(){
+        for source in RegisterTarget::iter() {
+            let mut cpu = CPU::new(Model::Dmg);
+            let value = u8::random();
+            cpu.registers.a = 0x01;
+            cpu.set_register_value(source, value);
+
+            cpu.or_register(source);
+
+            let expected = if source == RegisterTarget::A { value } else { 0x01 | value };
+            assert_eq!(expected, cpu.registers.a);
+        }
+    }
+
+    #[test]
+    fn test_or_hl_and_n(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        let hl_address = 0xC234;
+        cpu.registers.set_hl(hl_address);
+        cpu.bus.write_byte(hl_address, 0x0F);
+        cpu.bus.write_byte(0xC000, 0x30);
+
+        cpu.or_indirect_hl();
+
+        assert_eq!(0x0F, cpu.registers.a);
+
+        cpu.or_n();
+
+        assert_eq!(0x3F, cpu.registers.a);
+        assert_eq!(0xC001, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_xor_r(){
+        for source in RegisterTarget::iter() {
+            let mut cpu = CPU::new(Model::Dmg);
+            let value = u8::random();
+            cpu.registers.a = 0xFF;
+            cpu.set_register_value(source, value);
+
+            cpu.xor_register(source);
+
+            let expected = if source == RegisterTarget::A { 0x0 } else { 0xFF ^ value };
+            assert_eq!(expected, cpu.registers.a);
+            assert_eq!(expected == 0, cpu.registers.f.zero);
+        }
+    }
+
+    #[test]
+    fn test_xor_hl_and_n(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        let hl_address = 0xC234;
+        cpu.registers.set_hl(hl_address);
+        cpu.bus.write_byte(hl_address, 0x0F);
+        cpu.bus.write_byte(0xC000, 0xFF);
+
+        cpu.xor_indirect_hl();
+
+        assert_eq!(0x0F, cpu.registers.a);
+
+        cpu.xor_n();
+
+        assert_eq!(0xF0, cpu.registers.a);
+        assert_eq!(0xC001, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_cp_r(){
+        for source in RegisterTarget::iter() {
+            let mut cpu = CPU::new(Model::Dmg);
+            let value = u8::random();
+            cpu.registers.a = 0x80;
+            cpu.set_register_value(source, value);
+
+            cpu.compare_register(source);
+
+            let a = cpu.registers.a;
+            assert_eq!(if source == RegisterTarget::A { value } else { 0x80 }, a);
+            assert_eq!(a == cpu.get_register_value(source), cpu.registers.f.zero);
+            assert!(cpu.registers.f.subtract);
+        }
+    }
+
+    #[test]
+    fn test_cp_hl_and_n(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        let hl_address = 0xC234;
+        cpu.registers.set_hl(hl_address);
+        cpu.bus.write_byte(hl_address, 0x20);
+        cpu.bus.write_byte(0xC000, 0x10);
+        cpu.registers.a = 0x10;
+
+        cpu.compare_indirect_hl();
+
+        assert_eq!(0x10, cpu.registers.a);
+        assert!(cpu.registers.f.carry);
+
+        cpu.compare_n();
+
+        assert_eq!(0x10, cpu.registers.a);
+        assert!(cpu.registers.f.zero);
+        assert!(!cpu.registers.f.carry);
+        assert_eq!(0xC001, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_increment_constant(){
+        let mut cpu = CPU::new(Model::Dmg);
+
+        assert_eq!(0x1, cpu.increment_constant(0x0));
+        assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+
+        assert_eq!(0x10, cpu.increment_constant(0x0F));
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: true,
+            carry: false
+        }, cpu.registers.f);
+
+        assert_eq!(0x0, cpu.increment_constant(0xFF));
+        assert_eq!(FlagRegister{
+            zero: true,
+            subtract: false,
+            half_carry: true,
+            carry: false
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_decrement_constant(){
+        let mut cpu = CPU::new(Model::Dmg);
+
+        assert_eq!(0x0, cpu.decrement_constant(0x1));
+        assert_eq!(FlagRegister{
+            zero: true,
+            subtract: true,
+            half_carry: false,
+            carry: false
+        }, cpu.registers.f);
+
+        assert_eq!(0x0F, cpu.decrement_constant(0x10));
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: true,
+            half_carry: true,
+            carry: false
+        }, cpu.registers.f);
+
+        assert_eq!(0xFF, cpu.decrement_constant(0x0));
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: true,
+            half_carry: true,
+            carry: false
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_inc_dec_keep_carry(){
+        for carry in [false, true] {
+            let mut cpu = CPU::new(Model::Dmg);
+            cpu.registers.f.carry = carry;
+
+            cpu.increment_constant(0xFF);
+
+            assert_eq!(carry, cpu.registers.f.carry);
+
+            cpu.decrement_constant(0x0);
+
+            assert_eq!(carry, cpu.registers.f.carry);
+        }
+    }
+
+    #[test]
+    fn test_inc_r(){
+        for target in RegisterTarget::iter() {
+            let mut cpu = CPU::new(Model::Dmg);
+            let value = u8::random();
+            cpu.set_register_value(target, value);
+
+            cpu.increment_register(target);
+
+            assert_eq!(value.wrapping_add(1), cpu.get_register_value(target));
+            assert!(!cpu.registers.f.subtract);
+            assert_eq!((value & 0xF) == 0xF, cpu.registers.f.half_carry);
+            assert!(!cpu.registers.f.carry);
+            for other in RegisterTarget::iter().filter(|other| *other != target) {
+                assert_eq!(0x0, cpu.get_register_value(other));
+            }
+        }
+    }
+
+    #[test]
+    fn test_dec_r(){
+        for target in RegisterTarget::iter() {
+            let mut cpu = CPU::new(Model::Dmg);
+            let value = u8::random();
+            cpu.set_register_value(target, value);
+
+            cpu.decrement_register(target);
+
+            assert_eq!(value.wrapping_sub(1), cpu.get_register_value(target));
+            assert!(cpu.registers.f.subtract);
+            assert_eq!((value & 0xF) == 0x0, cpu.registers.f.half_carry);
+            assert!(!cpu.registers.f.carry);
+            for other in RegisterTarget::iter().filter(|other| *other != target) {
+                assert_eq!(0x0, cpu.get_register_value(other));
+            }
+        }
+    }
+
+    #[test]
+    fn test_inc_hl(){
+        let mut cpu = CPU::new(Model::Dmg);
+        let hl_address = 0xC234;
+        cpu.registers.set_hl(hl_address);
+        cpu.bus.write_byte(hl_address, 0xFF);
+        cpu.registers.f.carry = true;
+
+        cpu.increment_indirect_hl();
+
+        assert_eq!(0x0, cpu.bus.read_byte(hl_address));
+        assert_eq!(hl_address, cpu.registers.get_hl());
+        assert_eq!(FlagRegister{
+            zero: true,
+            subtract: false,
+            half_carry: true,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_dec_hl(){
+        let mut cpu = CPU::new(Model::Dmg);
+        let hl_address = 0xC234;
+        cpu.registers.set_hl(hl_address);
+        cpu.bus.write_byte(hl_address, 0x20);
+
+        cpu.decrement_indirect_hl();
+
+        assert_eq!(0x1F, cpu.bus.read_byte(hl_address));
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: true,
+            half_carry: true,
+            carry: false
+        }, cpu.registers.f);
+    }
+
+    // Reference DAA, written as the range table from the Z80 documentation for additions.
+    // After subtractions the Game Boy only looks at the flags, and half carry is always reset.
+    fn reference_daa(a: u8, subtract: bool, half_carry: bool, carry: bool) -> (u8, FlagRegister) {
+        let high = a >> 4;
+        let low = a & 0xF;
+        let (correction, new_carry) = if subtract {
+            ((if carry { 0x60 } else { 0x0 }) | (if half_carry { 0x06 } else { 0x0 }), carry)
+        } else {
+            match (carry, high, half_carry, low) {
+                (false, 0x0..=0x9, false, 0x0..=0x9) => (0x00, false),
+                (false, 0x0..=0x9, true, 0x0..=0x9) => (0x06, false),
+                (false, 0x0..=0x8, _, 0xA..=0xF) => (0x06, false),
+                (false, 0xA..=0xF, false, 0x0..=0x9) => (0x60, true),
+                (true, _, false, 0x0..=0x9) => (0x60, true),
+                (true, _, true, 0x0..=0x9) => (0x66, true),
+                (true, _, _, 0xA..=0xF) => (0x66, true),
+                (false, 0x9..=0xF, _, 0xA..=0xF) => (0x66, true),
+                (false, 0xA..=0xF, true, 0x0..=0x9) => (0x66, true),
+                _ => unreachable!()
+            }
+        };
+        let result = if subtract { a.wrapping_sub(correction) } else { a.wrapping_add(correction) };
+        (result, FlagRegister{
+            zero: result == 0,
+            subtract,
+            half_carry: false,
+            carry: new_carry
+        })
+    }
+
+    #[test]
+    fn test_decimal_adjust_a_exhaustive(){
+        for a in 0..=u8::MAX {
+            for flags in 0..8u8 {
+                let subtract = flags & 0b100 != 0;
+                let half_carry = flags & 0b010 != 0;
+                let carry = flags & 0b001 != 0;
+                let mut cpu = CPU::new(Model::Dmg);
+                cpu.registers.a = a;
+                cpu.registers.f = FlagRegister{
+                    zero: false,
+                    subtract,
+                    half_carry,
+                    carry
+                };
+
+                cpu.decimal_adjust_a();
+
+                let (expected_a, expected_flags) = reference_daa(a, subtract, half_carry, carry);
+                assert_eq!(expected_a, cpu.registers.a, "a: {:#04x}, n: {}, h: {}, c: {}", a, subtract, half_carry, carry);
+                assert_eq!(expected_flags, cpu.registers.f, "a: {:#04x}, n: {}, h: {}, c: {}", a, subtract, half_carry, carry);
+            }
+        }
+    }
+
+    fn to_bcd(value: u8) -> u8 {
+        (value / 10) << 4 | (value % 10)
+    }
+
+    #[test]
+    fn test_decimal_adjust_a_after_bcd_arithmetic(){
+        for x in 0..100u8 {
+            for y in 0..100u8 {
+                let mut cpu = CPU::new(Model::Dmg);
+                cpu.registers.a = to_bcd(x);
+
+                cpu.add_constant(to_bcd(y));
+                cpu.decimal_adjust_a();
+
+                assert_eq!(to_bcd((x + y) % 100), cpu.registers.a);
+                assert_eq!(x + y >= 100, cpu.registers.f.carry);
+
+                cpu.registers.a = to_bcd(x);
+
+                cpu.sub_constant(to_bcd(y));
+                cpu.decimal_adjust_a();
+
+                assert_eq!(to_bcd((100 + x - y) % 100), cpu.registers.a);
+                assert_eq!(x < y, cpu.registers.f.carry);
+                assert_eq!(x == y, cpu.registers.f.zero);
+            }
+        }
+    }
+
+    #[test]
+    fn test_complement_a(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x35;
+        cpu.registers.f.zero = true;
+        cpu.registers.f.carry = true;
+
+        cpu.complement_a();
+
+        assert_eq!(0xCA, cpu.registers.a);
+        assert_eq!(FlagRegister::from(0xF0), cpu.registers.f);
+    }
+
+    #[test]
+    fn test_set_carry_flag(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.f = FlagRegister::from(0xE0);
+
+        cpu.set_carry_flag();
+
+        assert_eq!(FlagRegister{
+            zero: true,
+            subtract: false,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_complement_carry_flag(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.f = FlagRegister::from(0xF0);
+
+        cpu.complement_carry_flag();
+
+        assert_eq!(FlagRegister{
+            zero: true,
+            subtract: false,
+            half_carry: false,
+            carry: false
+        }, cpu.registers.f);
+
+        cpu.complement_carry_flag();
+
+        assert!(cpu.registers.f.carry);
+    }
+}
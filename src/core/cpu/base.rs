@@ -1,40 +1,222 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use crate::core::boot::BootRom;
+use crate::core::cartridge::base::{Cartridge, CartridgeError};
+use crate::core::cartridge::peripheral::Peripheral;
 use crate::core::instructions::definitions::Instruction;
 use crate::core::memory::MemoryBus;
+use crate::core::model::Model;
 use crate::core::registers::Registers;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TimingMode {
+    // The other components catch up once the whole instruction has been executed
+    Instruction,
+    // The other components advance before every M-cycle of the instruction, so that each bus
+    // access lands on the same cycle it would on hardware
+    MCycle
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CpuState {
+    Running,
+    // Entered by HALT, until an interrupt is pending
+    Halted,
+    // Entered by STOP, until a selected joypad line goes low
+    Stopped,
+    // Entered by STOP when a CGB speed switch was armed
+    SwitchingSpeed { remaining_cycles: u16 },
+    // Entered by executing an illegal opcode. Nothing but a reset gets the CPU out of it.
+    Locked { opcode: u8, address: u16 }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CpuError {
+    // One of the opcodes without an instruction was fetched, locking up the CPU
+    IllegalOpcode { opcode: u8, address: u16 }
+}
+
+impl Display for CpuError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CpuError::IllegalOpcode { opcode, address } =>
+                write!(f, "illegal opcode {:#04x} at {:#06x}, the CPU is locked", opcode, address)
+        }
+    }
+}
+
+impl Error for CpuError {}
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug)]
 pub struct CPU {
     pub(super) registers: Registers,
     pub(super) program_counter: u16,
     pub(super) stack_pointer: u16,
-    pub(super) bus: MemoryBus
+    pub(super) bus: MemoryBus,
+    pub(super) interrupt_master_enable: bool,
+    // Set by EI, IME gets enabled once the next instruction has been executed
+    pub(super) interrupt_master_enable_scheduled: bool,
+    pub(super) state: CpuState,
+    // Set when HALT is skipped, makes the next opcode fetch not increment pc
+    pub(super) halt_bug: bool,
+    // M-cycles elapsed since power on
+    pub(super) cycles: u64,
+    pub(super) timing_mode: TimingMode,
+    // Every bus access made by the CPU, with the bus cycle it happened on
+    #[cfg(test)]
+    pub(super) bus_accesses: Vec<BusAccess>
 }
+
+#[cfg(test)]
+#[derive(Debug, PartialEq)]
+pub(super) enum BusAccess {
+    Read { cycle: u64, address: u16 },
+    Write { cycle: u64, address: u16, value: u8 }
+}
+
 impl CPU {
-    pub (super) fn new() -> Self {
+    pub (super) fn new(model: Model) -> Self {
         CPU{
             registers: Registers::new(),
             program_counter: 0,
             stack_pointer:0,
-            bus: MemoryBus::new()
+            bus: MemoryBus::new(model),
+            interrupt_master_enable: false,
+            interrupt_master_enable_scheduled: false,
+            state: CpuState::Running,
+            halt_bug: false,
+            cycles: 0,
+            timing_mode: TimingMode::Instruction,
+            #[cfg(test)]
+            bus_accesses: Vec::new()
+        }
+    }
+
+    // Starts right where the boot ROM would have handed over to the cartridge
+    pub (crate) fn with_cartridge(model: Model, cartridge: Cartridge) -> Self {
+        let mut cpu = CPU::new(model);
+        cpu.bus.insert_cartridge(cartridge);
+        cpu.skip_boot_rom();
+        cpu
+    }
+
+    // Starts from power on, running the boot ROM first
+    pub (crate) fn with_boot_rom(model: Model, cartridge: Cartridge, boot_rom: BootRom) -> Self {
+        let mut cpu = CPU::new(model);
+        cpu.bus.insert_cartridge(cartridge);
+        cpu.bus.map_boot_rom(boot_rom);
+        cpu
+    }
+
+    fn skip_boot_rom(&mut self) {
+        self.bus.skip_boot_rom();
+        self.registers = Registers::post_boot(self.bus.model, self.bus.header(), self.bus.cgb_mode);
+        self.stack_pointer = 0xFFFE;
+        self.program_counter = 0x0100;
+    }
+
+    // Not selectable from the command line yet
+    #[allow(dead_code)]
+    pub (crate) fn set_timing_mode(&mut self, timing_mode: TimingMode) {
+        self.timing_mode = timing_mode;
+    }
+
+    pub (crate) fn load_save(&mut self) -> Result<(), CartridgeError> {
+        self.bus.load_save()
+    }
+
+    pub (crate) fn flush_save(&self) -> Result<(), CartridgeError> {
+        self.bus.flush_save()
+    }
+
+    // Meant to be called after every step
+    pub (crate) fn flush_due_save(&mut self) -> Result<(), CartridgeError> {
+        self.bus.flush_due_save()
+    }
+
+    // Can be called while running, e.g. whenever the host reports a new tilt
+    pub (crate) fn connect(&mut self, peripheral: Peripheral) -> Result<(), CartridgeError> {
+        self.bus.connect(peripheral)
+    }
+
+    // Serves a pending interrupt if IME is set, otherwise executes the next instruction.
+    // While halted, stopped or locked, it waits for a single M-cycle instead.
+    // Returns the M-cycles it took, or the error that locked the CPU up during this step.
+    // The other components keep running after the CPU is locked, so stepping can go on.
+    pub (crate) fn step(&mut self) -> Result<u8, CpuError> {
+        let start_cycles = self.cycles;
+        let result = match self.state {
+            CpuState::Running => self.step_running(),
+            CpuState::Halted => self.step_halted(),
+            CpuState::Stopped => self.step_stopped(),
+            CpuState::SwitchingSpeed { remaining_cycles } => {
+                self.step_switching_speed(remaining_cycles);
+                Ok(())
+            }
+            CpuState::Locked { .. } => {
+                self.tick();
+                Ok(())
+            }
+        };
+        let cycles = (self.cycles - start_cycles) as u8;
+        if self.timing_mode == TimingMode::Instruction {
+            self.bus.tick(cycles);
         }
+        result.map(|_| cycles)
     }
 
-    fn step(&mut self){
-        let mut instruction_byte = self.read_byte_and_increment_pc();
+    pub (super) fn step_running(&mut self) -> Result<(), CpuError> {
+        if self.should_dispatch_interrupt() {
+            self.dispatch_interrupt();
+            Ok(())
+        } else {
+            self.fetch_and_execute()
+        }
+    }
+
+    fn fetch_and_execute(&mut self) -> Result<(), CpuError> {
+        let enable_interrupts = self.interrupt_master_enable_scheduled;
+        let address = self.program_counter;
+        let mut instruction_byte = if self.halt_bug {
+            self.halt_bug = false;
+            self.read_byte(self.program_counter)
+        } else {
+            self.read_byte_and_increment_pc()
+        };
         let is_prefixed = instruction_byte == 0xCB;
         if is_prefixed {
             instruction_byte = self.read_byte_and_increment_pc();
         }
-        if let Some(instruction) = Instruction::from_byte(instruction_byte, is_prefixed){
-            self.execute(instruction);
-        } else {
-            let description = format!("0x{}{:x}", if is_prefixed { "cb" } else { "" }, instruction_byte);
-            panic!("Unknown instruction for: 0x{}", description)
+        // Every prefixed opcode is mapped, so only unprefixed ones can be illegal
+        let Some(instruction) = Instruction::from_byte(instruction_byte, is_prefixed) else {
+            self.state = CpuState::Locked { opcode: instruction_byte, address };
+            return Err(CpuError::IllegalOpcode { opcode: instruction_byte, address });
         };
+        self.execute(instruction);
+        // DI right after EI cancels the scheduled enable
+        if enable_interrupts && self.interrupt_master_enable_scheduled {
+            self.interrupt_master_enable = true;
+            self.interrupt_master_enable_scheduled = false;
+        }
+        Ok(())
     }
 
     fn execute(&mut self, instruction: Instruction) {
         match instruction {
+            Instruction::NoOperation => {}
+            Instruction::DisableInterrupts => {
+                self.disable_interrupts();
+            }
+            Instruction::EnableInterrupts => {
+                self.enable_interrupts();
+            }
+            Instruction::Halt => {
+                self.halt();
+            }
+            Instruction::Stop => {
+                self.stop();
+            }
             // 8 bit arithmetic
             Instruction::AddRegister(target) => {
                 self.add_register(target);
@@ -54,8 +236,114 @@ impl CPU {
             Instruction::AddCarryN => {
                 self.add_carry_n();
             }
+            Instruction::SubRegister(target) => {
+                self.sub_register(target);
+            }
+            Instruction::SubIndirectHl => {
+                self.sub_indirect_hl();
+            }
+            Instruction::SubN => {
+                self.sub_n();
+            }
+            Instruction::SubCarryRegister(target) => {
+                self.sub_carry_register(target);
+            }
+            Instruction::SbcIndirectHl => {
+                self.sub_carry_indirect_hl();
+            }
+            Instruction::SubCarryN => {
+                self.sub_carry_n();
+            }
+            Instruction::AndRegister(target) => {
+                self.and_register(target);
+            }
+            Instruction::AndIndirectHl => {
+                self.and_indirect_hl();
+            }
+            Instruction::AndN => {
+                self.and_n();
+            }
+            Instruction::OrRegister(target) => {
+                self.or_register(target);
+            }
+            Instruction::OrIndirectHl => {
+                self.or_indirect_hl();
+            }
+            Instruction::OrN => {
+                self.or_n();
+            }
+            Instruction::XorRegister(target) => {
+                self.xor_register(target);
+            }
+            Instruction::XorIndirectHl => {
+                self.xor_indirect_hl();
+            }
+            Instruction::XorN => {
+                self.xor_n();
+            }
+            Instruction::CompareRegister(target) => {
+                self.compare_register(target);
+            }
+            Instruction::CompareIndirectHl => {
+                self.compare_indirect_hl();
+            }
+            Instruction::CompareN => {
+                self.compare_n();
+            }
+            Instruction::IncrementRegister(target) => {
+                self.increment_register(target);
+            }
+            Instruction::IncrementIndirectHl => {
+                self.increment_indirect_hl();
+            }
+            Instruction::DecrementRegister(target) => {
+                self.decrement_register(target);
+            }
+            Instruction::DecrementIndirectHl => {
+                self.decrement_indirect_hl();
+            }
+            Instruction::DecimalAdjustA => {
+                self.decimal_adjust_a();
+            }
+            Instruction::ComplementA => {
+                self.complement_a();
+            }
+            Instruction::SetCarryFlag => {
+                self.set_carry_flag();
+            }
+            Instruction::ComplementCarryFlag => {
+                self.complement_carry_flag();
+            }
+            Instruction::RotateLeftCircularA => {
+                self.rotate_left_circular_a();
+            }
+            Instruction::RotateRightCircularA => {
+                self.rotate_right_circular_a();
+            }
+            Instruction::RotateLeftA => {
+                self.rotate_left_a();
+            }
+            Instruction::RotateRightA => {
+                self.rotate_right_a();
+            }
+            // 16 bit arithmetic
+            Instruction::AddHlRegister16(source) => {
+                self.add_hl_register16(source);
+            }
+            Instruction::IncrementRegister16(target) => {
+                self.increment_register16(target);
+            }
+            Instruction::DecrementRegister16(target) => {
+                self.decrement_register16(target);
+            }
+            Instruction::AddStackPointerE => {
+                self.add_stack_pointer_e();
+            }
+            Instruction::LoadHlStackPointerE => {
+                self.load_hl_stack_pointer_e();
+            }
             // 8 bit load
-            Instruction::LoadRegisterRegister(source, receiver) => {
+            Instruction::LoadRegisterRegister(receiver, source) => {
                 self.load_register_register(source, receiver);
             },
             Instruction::LoadRegisterN(receiver) => {
@@ -134,6 +422,100 @@ impl CPU {
             Instruction::JumpConditionalToNn(jump_condition) => {
                 self.jump_conditional_to_nn(jump_condition);
             }
+            Instruction::JumpToHl => {
+                self.jump_to_hl();
+            }
+            Instruction::JumpRelative => {
+                self.jump_relative();
+            }
+            Instruction::JumpConditionalRelative(jump_condition) => {
+                self.jump_conditional_relative(jump_condition);
+            }
+            Instruction::CallNn => {
+                self.call_nn();
+            }
+            Instruction::CallConditionalNn(jump_condition) => {
+                self.call_conditional_nn(jump_condition);
+            }
+            Instruction::Return => {
+                self.return_from_call();
+            }
+            Instruction::ReturnConditional(jump_condition) => {
+                self.return_conditional(jump_condition);
+            }
+            Instruction::ReturnInterrupt => {
+                self.return_interrupt();
+            }
+            Instruction::Restart(address) => {
+                self.restart(address);
+            }
+            // prefixed instructions
+            Instruction::RotateLeftCircularRegister(target) => {
+                self.rotate_left_circular_register(target);
+            }
+            Instruction::RotateLeftCircularIndirectHl => {
+                self.rotate_left_circular_indirect_hl();
+            }
+            Instruction::RotateRightCircularRegister(target) => {
+                self.rotate_right_circular_register(target);
+            }
+            Instruction::RotateRightCircularIndirectHl => {
+                self.rotate_right_circular_indirect_hl();
+            }
+            Instruction::RotateLeftRegister(target) => {
+                self.rotate_left_register(target);
+            }
+            Instruction::RotateLeftIndirectHl => {
+                self.rotate_left_indirect_hl();
+            }
+            Instruction::RotateRightRegister(target) => {
+                self.rotate_right_register(target);
+            }
+            Instruction::RotateRightIndirectHl => {
+                self.rotate_right_indirect_hl();
+            }
+            Instruction::ShiftLeftArithmeticRegister(target) => {
+                self.shift_left_arithmetic_register(target);
+            }
+            Instruction::ShiftLeftArithmeticIndirectHl => {
+                self.shift_left_arithmetic_indirect_hl();
+            }
+            Instruction::ShiftRightArithmeticRegister(target) => {
+                self.shift_right_arithmetic_register(target);
+            }
+            Instruction::ShiftRightArithmeticIndirectHl => {
+                self.shift_right_arithmetic_indirect_hl();
+            }
+            Instruction::SwapRegister(target) => {
+                self.swap_register(target);
+            }
+            Instruction::SwapIndirectHl => {
+                self.swap_indirect_hl();
+            }
+            Instruction::ShiftRightLogicalRegister(target) => {
+                self.shift_right_logical_register(target);
+            }
+            Instruction::ShiftRightLogicalIndirectHl => {
+                self.shift_right_logical_indirect_hl();
+            }
+            Instruction::BitRegister(bit, target) => {
+                self.bit_register(bit, target);
+            }
+            Instruction::BitIndirectHl(bit) => {
+                self.bit_indirect_hl(bit);
+            }
+            Instruction::ResetRegister(bit, target) => {
+                self.reset_register(bit, target);
+            }
+            Instruction::ResetIndirectHl(bit) => {
+                self.reset_indirect_hl(bit);
+            }
+            Instruction::SetRegister(bit, target) => {
+                self.set_register(bit, target);
+            }
+            Instruction::SetIndirectHl(bit) => {
+                self.set_indirect_hl(bit);
+            }
         }
     }
 
@@ -141,19 +523,161 @@ impl CPU {
 
 #[cfg(test)]
 mod test{
-    use crate::core::cpu::base::CPU;
+    use std::{env, fs, process};
+    use crate::core::boot::BootRom;
+    use crate::core::cartridge::base::{Cartridge, CartridgeError};
+    use crate::core::cartridge::header::{fix_checksums, test_rom};
+    use crate::core::cartridge::peripheral::Peripheral;
+    use crate::core::cpu::base::{BusAccess, CPU, CpuError, CpuState, TimingMode};
+    use crate::core::cpu::base::BusAccess::{Read, Write};
     use crate::core::instructions::definitions::{Instruction, RegisterTarget};
+    use crate::core::interrupts::Interrupt;
+    use crate::core::model::Model;
+    use crate::core::registers::FlagRegister;
+
+    const PROGRAM_ADDRESS: u16 = 0xC000;
+
+    // M-cycles of every unprefixed opcode, taking conditional branches. 0 marks unmapped opcodes.
+    const CYCLES: [u8; 256] = [
+        1, 3, 2, 2, 1, 1, 2, 1, 5, 2, 2, 2, 1, 1, 2, 1, // 0x
+        1, 3, 2, 2, 1, 1, 2, 1, 3, 2, 2, 2, 1, 1, 2, 1, // 1x
+        3, 3, 2, 2, 1, 1, 2, 1, 3, 2, 2, 2, 1, 1, 2, 1, // 2x
+        3, 3, 2, 2, 3, 3, 3, 1, 3, 2, 2, 2, 1, 1, 2, 1, // 3x
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 4x
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 5x
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 6x
+        2, 2, 2, 2, 2, 2, 1, 2, 1, 1, 1, 1, 1, 1, 2, 1, // 7x
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 8x
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 9x
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // Ax
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // Bx
+        5, 3, 4, 4, 6, 4, 2, 4, 5, 4, 4, 0, 6, 6, 2, 4, // Cx
+        5, 3, 4, 0, 6, 4, 2, 4, 5, 4, 4, 0, 6, 0, 2, 4, // Dx
+        3, 3, 2, 0, 0, 4, 2, 4, 4, 1, 4, 0, 0, 0, 2, 4, // Ex
+        3, 3, 2, 1, 0, 4, 2, 4, 3, 2, 4, 1, 0, 0, 2, 4, // Fx
+    ];
+
+    // M-cycles of the conditional opcodes when the branch is not taken
+    fn not_taken_cycles(opcode: u8) -> u8 {
+        match opcode {
+            0x20 | 0x28 | 0x30 | 0x38 => 2,
+            0xC0 | 0xC8 | 0xD0 | 0xD8 => 2,
+            0xC2 | 0xCA | 0xD2 | 0xDA => 3,
+            0xC4 | 0xCC | 0xD4 | 0xDC => 3,
+            _ => CYCLES[opcode as usize]
+        }
+    }
+
+    // With all the flags reset NZ and NC are satisfied, with all of them set Z and C are
+    fn is_taken(opcode: u8, flags: u8) -> bool {
+        let satisfied_by_reset_flags = opcode & 0x08 == 0;
+        satisfied_by_reset_flags == (flags == 0x00)
+    }
+
+    fn cpu_with_program(program: &[u8]) -> CPU {
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = PROGRAM_ADDRESS;
+        cpu.stack_pointer = 0xDFF0;
+        cpu.registers.set_hl(0xC800);
+        for (offset, byte) in program.iter().enumerate() {
+            cpu.bus.write_byte(PROGRAM_ADDRESS + offset as u16, *byte);
+        }
+        cpu
+    }
 
     #[test]
     fn test_step(){
-        let cpu = CPU::new();
+        let mut cpu = cpu_with_program(&[0x3E, 0x12, 0x47, 0x00]);
+
+        assert_eq!(2, cpu.step().unwrap());
+        assert_eq!(0x12, cpu.registers.a);
+
+        assert_eq!(1, cpu.step().unwrap());
+        assert_eq!(0x12, cpu.registers.b);
+
+        assert_eq!(1, cpu.step().unwrap());
+        assert_eq!(PROGRAM_ADDRESS + 4, cpu.program_counter);
+        assert_eq!(4, cpu.cycles);
+    }
 
-        //cpu.step();
+    #[test]
+    fn test_step_loads_into_first_operand(){
+        // LD B,C; LD A,(HL); LD (HL),D; LD E,A
+        let mut cpu = cpu_with_program(&[0x41, 0x7E, 0x72, 0x5F]);
+        cpu.registers.b = 0x01;
+        cpu.registers.c = 0x02;
+        cpu.registers.d = 0x03;
+        cpu.bus.write_byte(0xC800, 0x04);
+
+        cpu.step().unwrap();
+
+        assert_eq!(0x02, cpu.registers.b);
+        assert_eq!(0x02, cpu.registers.c);
+
+        cpu.step().unwrap();
+
+        assert_eq!(0x04, cpu.registers.a);
+
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+
+        assert_eq!(0x03, cpu.bus.read_byte(0xC800));
+        assert_eq!(0x04, cpu.registers.e);
+    }
+
+    #[test]
+    fn test_step_cycles(){
+        for opcode in 0..=u8::MAX {
+            if CYCLES[opcode as usize] == 0 {
+                continue;
+            }
+            for flags in [0x00, 0xF0] {
+                let mut cpu = cpu_with_program(&[opcode, 0x00, 0xD0]);
+                cpu.registers.f = FlagRegister::from(flags);
+
+                let cycles = cpu.step().unwrap();
+
+                let expected = if is_taken(opcode, flags) { CYCLES[opcode as usize] } else { not_taken_cycles(opcode) };
+                assert_eq!(expected, cycles, "opcode: {:#04x}, flags: {:#04x}", opcode, flags);
+                assert_eq!(expected as u64, cpu.cycles, "opcode: {:#04x}, flags: {:#04x}", opcode, flags);
+            }
+        }
+    }
+
+    #[test]
+    fn test_step_prefixed_cycles(){
+        for opcode in 0..=u8::MAX {
+            let mut cpu = cpu_with_program(&[0xCB, opcode]);
+            let is_indirect_hl = opcode & 0b111 == 0b110;
+            let is_bit = (0x40..0x80).contains(&opcode);
+
+            let cycles = cpu.step().unwrap();
+
+            let expected = match (is_indirect_hl, is_bit) {
+                (false, _) => 2,
+                (true, true) => 3,
+                (true, false) => 4
+            };
+            assert_eq!(expected, cycles, "opcode: 0xcb{:02x}", opcode);
+        }
+    }
+
+    #[test]
+    fn test_cycles_accumulate(){
+        // LD BC,nn; JP nn (to itself)
+        let mut cpu = cpu_with_program(&[0x01, 0x34, 0x12, 0xC3, 0x03, 0xC0]);
+
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+
+        assert_eq!(3 + 4 + 4, cpu.cycles);
+        assert_eq!(PROGRAM_ADDRESS + 3, cpu.program_counter);
     }
 
     #[test]
     fn test_execute(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         cpu.registers.a = 0x1;
 
         cpu.execute(Instruction::AddRegister(RegisterTarget::A));
@@ -161,4 +685,412 @@ mod test{
         assert_eq!(0x2, cpu.registers.a);
     }
 
-}
\ No newline at end of file
+    fn run_m_cycle(program: &[u8]) -> (CPU, Vec<BusAccess>) {
+        let mut cpu = cpu_with_program(program);
+        cpu.set_timing_mode(TimingMode::MCycle);
+        cpu.step().unwrap();
+        let accesses = std::mem::take(&mut cpu.bus_accesses);
+        (cpu, accesses)
+    }
+
+    #[test]
+    fn test_m_cycle_timing_load_nn_from_stack_pointer(){
+        let (cpu, accesses) = run_m_cycle(&[0x08, 0x00, 0xC1]);
+
+        assert_eq!(vec![
+            Read { cycle: 1, address: 0xC000 },
+            Read { cycle: 2, address: 0xC001 },
+            Read { cycle: 3, address: 0xC002 },
+            Write { cycle: 4, address: 0xC100, value: 0xF0 },
+            Write { cycle: 5, address: 0xC101, value: 0xDF },
+        ], accesses);
+        assert_eq!(5, cpu.bus.cycles);
+    }
+
+    #[test]
+    fn test_m_cycle_timing_push(){
+        let mut cpu = cpu_with_program(&[0xC5]);
+        cpu.set_timing_mode(TimingMode::MCycle);
+        cpu.registers.set_bc(0x1234);
+
+        cpu.step().unwrap();
+
+        assert_eq!(vec![
+            Read { cycle: 1, address: 0xC000 },
+            Write { cycle: 3, address: 0xDFEF, value: 0x12 },
+            Write { cycle: 4, address: 0xDFEE, value: 0x34 },
+        ], cpu.bus_accesses);
+        assert_eq!(4, cpu.bus.cycles);
+    }
+
+    #[test]
+    fn test_m_cycle_timing_pop(){
+        let (_, accesses) = run_m_cycle(&[0xC1]);
+
+        assert_eq!(vec![
+            Read { cycle: 1, address: 0xC000 },
+            Read { cycle: 2, address: 0xDFF0 },
+            Read { cycle: 3, address: 0xDFF1 },
+        ], accesses);
+    }
+
+    #[test]
+    fn test_m_cycle_timing_call(){
+        let (cpu, accesses) = run_m_cycle(&[0xCD, 0x00, 0xC1]);
+
+        assert_eq!(vec![
+            Read { cycle: 1, address: 0xC000 },
+            Read { cycle: 2, address: 0xC001 },
+            Read { cycle: 3, address: 0xC002 },
+            Write { cycle: 5, address: 0xDFEF, value: 0xC0 },
+            Write { cycle: 6, address: 0xDFEE, value: 0x03 },
+        ], accesses);
+        assert_eq!(0xC100, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_m_cycle_timing_read_modify_write(){
+        // INC (HL), then SET 0,(HL)
+        let mut cpu = cpu_with_program(&[0x34, 0xCB, 0xC6]);
+        cpu.set_timing_mode(TimingMode::MCycle);
+
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+
+        assert_eq!(vec![
+            Read { cycle: 1, address: 0xC000 },
+            Read { cycle: 2, address: 0xC800 },
+            Write { cycle: 3, address: 0xC800, value: 0x01 },
+            Read { cycle: 4, address: 0xC001 },
+            Read { cycle: 5, address: 0xC002 },
+            Read { cycle: 6, address: 0xC800 },
+            Write { cycle: 7, address: 0xC800, value: 0x01 },
+        ], cpu.bus_accesses);
+    }
+
+    #[test]
+    fn test_m_cycle_timing_return(){
+        let mut cpu = cpu_with_program(&[0xC9]);
+        cpu.set_timing_mode(TimingMode::MCycle);
+        cpu.bus.write_word(0xDFF0, 0xC123);
+
+        let cycles = cpu.step().unwrap();
+
+        assert_eq!(vec![
+            Read { cycle: 1, address: 0xC000 },
+            Read { cycle: 2, address: 0xDFF0 },
+            Read { cycle: 3, address: 0xDFF1 },
+        ], cpu.bus_accesses);
+        assert_eq!(4, cycles);
+        assert_eq!(4, cpu.bus.cycles);
+    }
+
+    #[test]
+    fn test_instruction_timing(){
+        let mut cpu = cpu_with_program(&[0x08, 0x00, 0xC1]);
+
+        let cycles = cpu.step().unwrap();
+
+        assert!(cpu.bus_accesses.iter().all(|access| match access {
+            Read { cycle, .. } | Write { cycle, .. } => *cycle == 0
+        }));
+        assert_eq!(5, cycles);
+        assert_eq!(5, cpu.bus.cycles);
+    }
+
+    #[test]
+    fn test_step_dispatches_interrupt(){
+        let mut cpu = cpu_with_program(&[0x00]);
+        cpu.interrupt_master_enable = true;
+        cpu.bus.write_byte(0xFFFF, 0x04);
+        cpu.bus.request_interrupt(Interrupt::Timer);
+
+        assert_eq!(5, cpu.step().unwrap());
+        assert_eq!(0x50, cpu.program_counter);
+        assert_eq!(PROGRAM_ADDRESS, cpu.bus.read_word(cpu.stack_pointer));
+        assert!(!cpu.interrupt_master_enable);
+    }
+
+    #[test]
+    fn test_step_ignores_interrupt_without_ime(){
+        let mut cpu = cpu_with_program(&[0x00]);
+        cpu.bus.write_byte(0xFFFF, 0x04);
+        cpu.bus.request_interrupt(Interrupt::Timer);
+
+        assert_eq!(1, cpu.step().unwrap());
+        assert_eq!(PROGRAM_ADDRESS + 1, cpu.program_counter);
+        assert_eq!(0xE4, cpu.bus.read_byte(0xFF0F));
+    }
+
+    #[test]
+    fn test_ei_delay(){
+        // EI; NOP; NOP
+        let mut cpu = cpu_with_program(&[0xFB, 0x00, 0x00]);
+        cpu.bus.write_byte(0xFFFF, 0x01);
+        cpu.bus.request_interrupt(Interrupt::VBlank);
+
+        cpu.step().unwrap();
+
+        assert!(!cpu.interrupt_master_enable);
+
+        cpu.step().unwrap();
+
+        // the instruction after EI still runs before the interrupt is served
+        assert!(cpu.interrupt_master_enable);
+        assert_eq!(PROGRAM_ADDRESS + 2, cpu.program_counter);
+
+        cpu.step().unwrap();
+
+        assert_eq!(0x40, cpu.program_counter);
+        assert_eq!(PROGRAM_ADDRESS + 2, cpu.bus.read_word(cpu.stack_pointer));
+    }
+
+    #[test]
+    fn test_ei_di_cancels(){
+        // EI; DI; NOP
+        let mut cpu = cpu_with_program(&[0xFB, 0xF3, 0x00]);
+        cpu.bus.write_byte(0xFFFF, 0x01);
+        cpu.bus.request_interrupt(Interrupt::VBlank);
+
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+
+        assert!(!cpu.interrupt_master_enable);
+        assert_eq!(PROGRAM_ADDRESS + 3, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_reti_enables_immediately(){
+        let mut cpu = cpu_with_program(&[0xD9]);
+        cpu.bus.write_word(0xDFF0, 0xC100);
+        cpu.bus.write_byte(0xFFFF, 0x01);
+        cpu.bus.request_interrupt(Interrupt::VBlank);
+
+        cpu.step().unwrap();
+
+        assert_eq!(0xC100, cpu.program_counter);
+
+        cpu.step().unwrap();
+
+        assert_eq!(0x40, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_m_cycle_timing_interrupt_dispatch(){
+        let mut cpu = cpu_with_program(&[0x00]);
+        cpu.set_timing_mode(TimingMode::MCycle);
+        cpu.interrupt_master_enable = true;
+        cpu.bus.write_byte(0xFFFF, 0x01);
+        cpu.bus.request_interrupt(Interrupt::VBlank);
+
+        cpu.step().unwrap();
+
+        assert_eq!(vec![
+            Write { cycle: 3, address: 0xDFEF, value: 0xC0 },
+            Write { cycle: 4, address: 0xDFEE, value: 0x00 },
+        ], cpu.bus_accesses);
+        assert_eq!(5, cpu.bus.cycles);
+    }
+
+    #[test]
+    fn test_illegal_opcodes_lock_the_cpu(){
+        let illegal_opcodes = [0xD3, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC, 0xFD];
+        for opcode in illegal_opcodes {
+            let mut cpu = cpu_with_program(&[0x00, opcode, 0x00]);
+            cpu.step().unwrap();
+
+            let result = cpu.step();
+
+            assert_eq!(Err(CpuError::IllegalOpcode { opcode, address: PROGRAM_ADDRESS + 1 }), result);
+            assert_eq!(CpuState::Locked { opcode, address: PROGRAM_ADDRESS + 1 }, cpu.state);
+            assert_eq!(2, cpu.cycles);
+        }
+    }
+
+    #[test]
+    fn test_locked_cpu_ignores_interrupts(){
+        let mut cpu = cpu_with_program(&[0xD3, 0x00]);
+        cpu.interrupt_master_enable = true;
+        assert!(cpu.step().is_err());
+
+        cpu.bus.write_byte(0xFFFF, 0x01);
+        cpu.bus.request_interrupt(Interrupt::VBlank);
+
+        assert_eq!(1, cpu.step().unwrap());
+        assert_eq!(1, cpu.step().unwrap());
+        assert_eq!(PROGRAM_ADDRESS + 1, cpu.program_counter);
+        assert_eq!(3, cpu.bus.cycles);
+    }
+
+    #[test]
+    fn test_illegal_opcode_error_display(){
+        let error = CpuError::IllegalOpcode { opcode: 0xDD, address: 0x0150 };
+
+        assert_eq!("illegal opcode 0xdd at 0x0150, the CPU is locked", error.to_string());
+    }
+
+    #[test]
+    fn test_with_cartridge_skips_boot_rom(){
+        let cpu = CPU::with_cartridge(Model::Dmg, Cartridge::new(test_rom(0x00, 0x00, 0x00)).unwrap());
+
+        assert_eq!(0x01B0, cpu.registers.get_af());
+        assert_eq!(0x0013, cpu.registers.get_bc());
+        assert_eq!(0x00D8, cpu.registers.get_de());
+        assert_eq!(0x014D, cpu.registers.get_hl());
+        assert_eq!(0xFFFE, cpu.stack_pointer);
+        assert_eq!(0x0100, cpu.program_counter);
+        assert_eq!(0x91, cpu.bus.read_byte(0xFF40));
+    }
+
+    #[test]
+    fn test_post_boot_flags_follow_header_checksum(){
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        // Makes the header checksum 0
+        rom[0x0134] = rom[0x0134].wrapping_add(rom[0x014D]);
+        fix_checksums(&mut rom);
+
+        let cpu = CPU::with_cartridge(Model::Dmg, Cartridge::new(rom).unwrap());
+
+        assert_eq!(0x0180, cpu.registers.get_af());
+    }
+
+    #[test]
+    fn test_post_boot_registers_per_model(){
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        rom[0x0143] = 0x80;
+        fix_checksums(&mut rom);
+        // AF, BC, DE and HL, as checked by the boot_regs tests
+        let expected = [
+            (Model::Dmg0, [0x0100, 0xFF13, 0x00C1, 0x8403]),
+            (Model::Dmg, [0x01B0, 0x0013, 0x00D8, 0x014D]),
+            (Model::Mgb, [0xFFB0, 0x0013, 0x00D8, 0x014D]),
+            (Model::Sgb, [0x0100, 0x0014, 0x0000, 0xC060]),
+            (Model::Sgb2, [0xFF00, 0x0014, 0x0000, 0xC060]),
+            (Model::Cgb, [0x1180, 0x0000, 0xFF56, 0x000D]),
+            (Model::Agb, [0x1100, 0x0100, 0xFF56, 0x000D])
+        ];
+
+        for (model, [af, bc, de, hl]) in expected {
+            let cpu = CPU::with_cartridge(model, Cartridge::new(rom.clone()).unwrap());
+
+            assert_eq!(af, cpu.registers.get_af(), "{:?}", model);
+            assert_eq!(bc, cpu.registers.get_bc(), "{:?}", model);
+            assert_eq!(de, cpu.registers.get_de(), "{:?}", model);
+            assert_eq!(hl, cpu.registers.get_hl(), "{:?}", model);
+            assert_eq!(model.is_cgb(), cpu.bus.cgb_mode, "{:?}", model);
+        }
+    }
+
+    #[test]
+    fn test_post_boot_cgb_compatibility_mode(){
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        rom[0x0134..0x0144].copy_from_slice(b"TETRIS\0\0\0\0\0\0\0\0\0\0");
+        rom[0x014B] = 0x01;
+        fix_checksums(&mut rom);
+
+        let cpu = CPU::with_cartridge(Model::Cgb, Cartridge::new(rom.clone()).unwrap());
+
+        assert!(!cpu.bus.cgb_mode);
+        assert_eq!(0x1180, cpu.registers.get_af());
+        assert_eq!(0xDB00, cpu.registers.get_bc());
+        assert_eq!(0x0008, cpu.registers.get_de());
+        assert_eq!(0x007C, cpu.registers.get_hl());
+        assert_eq!(0xFF, cpu.bus.read_byte(0xFF4F));
+
+        let cpu = CPU::with_cartridge(Model::Agb, Cartridge::new(rom).unwrap());
+
+        assert_eq!(0x1100, cpu.registers.get_af());
+        assert_eq!(0xDC00, cpu.registers.get_bc());
+
+        let cpu = CPU::with_cartridge(Model::Cgb, Cartridge::new(test_rom(0x00, 0x00, 0x00)).unwrap());
+
+        assert_eq!(0x0000, cpu.registers.get_bc());
+    }
+
+    #[test]
+    fn test_with_boot_rom(){
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        rom[0x00FC] = 0x77;
+        fix_checksums(&mut rom);
+        // LD A,1; LDH (0x50),A
+        let mut boot_rom = vec![0x00; 0x100];
+        boot_rom[0xFC..].copy_from_slice(&[0x3E, 0x01, 0xE0, 0x50]);
+        let mut cpu = CPU::with_boot_rom(Model::Dmg, Cartridge::new(rom).unwrap(), BootRom::new(boot_rom, Model::Dmg).unwrap());
+
+        assert_eq!(0x0000, cpu.program_counter);
+        assert_eq!(0x00, cpu.registers.a);
+
+        cpu.program_counter = 0xFC;
+        cpu.step().unwrap();
+
+        assert_eq!(0x3E, cpu.bus.read_byte(0x00FC));
+
+        cpu.step().unwrap();
+
+        assert_eq!(0x77, cpu.bus.read_byte(0x00FC));
+        assert_eq!(0x0100, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_connect_tilt_between_latches(){
+        let mut cpu = CPU::with_cartridge(Model::Dmg, Cartridge::new(test_rom(0x22, 0x05, 0x00)).unwrap());
+        cpu.bus.write_byte(0x0000, 0x0A);
+        cpu.bus.write_byte(0x4000, 0x40);
+        // LD HL,0xA000; LD (HL),0x55; LD L,0x10; LD (HL),0xAA; LD L,0x20; LD A,(HL)
+        let program = [0x21, 0x00, 0xA0, 0x36, 0x55, 0x2E, 0x10, 0x36, 0xAA, 0x2E, 0x20, 0x7E];
+        for (offset, byte) in program.iter().enumerate() {
+            cpu.bus.write_byte(PROGRAM_ADDRESS + offset as u16, *byte);
+        }
+
+        for (x, expected) in [(1.0, 0x40), (0.0, 0xD0)] {
+            cpu.connect(Peripheral::Tilt { x, y: 0.0 }).unwrap();
+            cpu.program_counter = PROGRAM_ADDRESS;
+            for _ in 0..6 {
+                cpu.step().unwrap();
+            }
+
+            assert_eq!(expected, cpu.registers.a);
+        }
+    }
+
+    #[test]
+    fn test_connect_missing_hardware(){
+        let mut cpu = CPU::with_cartridge(Model::Dmg, Cartridge::new(test_rom(0x00, 0x00, 0x00)).unwrap());
+
+        let result = cpu.connect(Peripheral::Tilt { x: 0.0, y: 0.0 });
+
+        assert!(matches!(result, Err(CartridgeError::MissingHardware("accelerometer"))));
+    }
+
+    #[test]
+    fn test_save_written_when_ram_disabled(){
+        let directory = env::temp_dir().join(format!("rustyboy_ram_disabled_{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let rom_path = directory.join("game.gb");
+        let save_path = directory.join("game.sav");
+        fs::write(&rom_path, test_rom(0x03, 0x00, 0x02)).unwrap();
+        let mut cpu = CPU::with_cartridge(Model::Dmg, Cartridge::from_file(&rom_path).unwrap());
+        cpu.load_save().unwrap();
+        // LD A,0x0A; LD (0x0000),A; LD A,0x12; LD (0xA000),A; XOR A; LD (0x0000),A
+        let program = [0x3E, 0x0A, 0xEA, 0x00, 0x00, 0x3E, 0x12, 0xEA, 0x00, 0xA0, 0xAF, 0xEA, 0x00, 0x00];
+        for (offset, byte) in program.iter().enumerate() {
+            cpu.bus.write_byte(PROGRAM_ADDRESS + offset as u16, *byte);
+        }
+        cpu.program_counter = PROGRAM_ADDRESS;
+
+        for _ in 0..5 {
+            cpu.step().unwrap();
+            cpu.flush_due_save().unwrap();
+        }
+
+        assert!(!save_path.exists());
+
+        cpu.step().unwrap();
+        cpu.flush_due_save().unwrap();
+
+        assert_eq!(0x12, fs::read(&save_path).unwrap()[0]);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
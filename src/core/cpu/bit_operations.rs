@@ -0,0 +1,580 @@
+use crate::core::cpu::base::CPU;
+use crate::core::instructions::definitions::RegisterTarget;
+
+impl CPU {
+    // Matching actual instructions /////////////////////
+    pub(super) fn rotate_left_circular_register(&mut self, target: RegisterTarget) {
+        self.apply_to_register(target, CPU::rotate_left_circular);
+    }
+    pub(super) fn rotate_left_circular_indirect_hl(&mut self) {
+        self.apply_to_indirect_hl(CPU::rotate_left_circular);
+    }
+    pub(super) fn rotate_right_circular_register(&mut self, target: RegisterTarget) {
+        self.apply_to_register(target, CPU::rotate_right_circular);
+    }
+    pub(super) fn rotate_right_circular_indirect_hl(&mut self) {
+        self.apply_to_indirect_hl(CPU::rotate_right_circular);
+    }
+    pub(super) fn rotate_left_register(&mut self, target: RegisterTarget) {
+        self.apply_to_register(target, CPU::rotate_left);
+    }
+    pub(super) fn rotate_left_indirect_hl(&mut self) {
+        self.apply_to_indirect_hl(CPU::rotate_left);
+    }
+    pub(super) fn rotate_right_register(&mut self, target: RegisterTarget) {
+        self.apply_to_register(target, CPU::rotate_right);
+    }
+    pub(super) fn rotate_right_indirect_hl(&mut self) {
+        self.apply_to_indirect_hl(CPU::rotate_right);
+    }
+    pub(super) fn shift_left_arithmetic_register(&mut self, target: RegisterTarget) {
+        self.apply_to_register(target, CPU::shift_left_arithmetic);
+    }
+    pub(super) fn shift_left_arithmetic_indirect_hl(&mut self) {
+        self.apply_to_indirect_hl(CPU::shift_left_arithmetic);
+    }
+    pub(super) fn shift_right_arithmetic_register(&mut self, target: RegisterTarget) {
+        self.apply_to_register(target, CPU::shift_right_arithmetic);
+    }
+    pub(super) fn shift_right_arithmetic_indirect_hl(&mut self) {
+        self.apply_to_indirect_hl(CPU::shift_right_arithmetic);
+    }
+    pub(super) fn swap_register(&mut self, target: RegisterTarget) {
+        self.apply_to_register(target, CPU::swap);
+    }
+    pub(super) fn swap_indirect_hl(&mut self) {
+        self.apply_to_indirect_hl(CPU::swap);
+    }
+    pub(super) fn shift_right_logical_register(&mut self, target: RegisterTarget) {
+        self.apply_to_register(target, CPU::shift_right_logical);
+    }
+    pub(super) fn shift_right_logical_indirect_hl(&mut self) {
+        self.apply_to_indirect_hl(CPU::shift_right_logical);
+    }
+    pub(super) fn bit_register(&mut self, bit: u8, target: RegisterTarget) {
+        let value = self.get_register_value(target);
+        self.test_bit(bit, value);
+    }
+    pub(super) fn bit_indirect_hl(&mut self, bit: u8) {
+        let value = self.read_indirect_hl();
+        self.test_bit(bit, value);
+    }
+    pub(super) fn reset_register(&mut self, bit: u8, target: RegisterTarget) {
+        let value = self.get_register_value(target);
+        self.set_register_value(target, value & !(1 << bit));
+    }
+    pub(super) fn reset_indirect_hl(&mut self, bit: u8) {
+        let address = self.registers.get_hl();
+        let value = self.read_byte(address);
+        self.write_byte(address, value & !(1 << bit));
+    }
+    pub(super) fn set_register(&mut self, bit: u8, target: RegisterTarget) {
+        let value = self.get_register_value(target);
+        self.set_register_value(target, value | (1 << bit));
+    }
+    pub(super) fn set_indirect_hl(&mut self, bit: u8) {
+        let address = self.registers.get_hl();
+        let value = self.read_byte(address);
+        self.write_byte(address, value | (1 << bit));
+    }
+
+    // The accumulator rotates always reset zero, unlike their prefixed counterparts
+    pub(super) fn rotate_left_circular_a(&mut self) {
+        self.apply_to_a(CPU::rotate_left_circular);
+    }
+    pub(super) fn rotate_right_circular_a(&mut self) {
+        self.apply_to_a(CPU::rotate_right_circular);
+    }
+    pub(super) fn rotate_left_a(&mut self) {
+        self.apply_to_a(CPU::rotate_left);
+    }
+    pub(super) fn rotate_right_a(&mut self) {
+        self.apply_to_a(CPU::rotate_right);
+    }
+
+    //////////////////////////////////////////////////////
+
+    // HELPER FUNCTIONS FOR INSTRUCTIONS /////////////////////
+    fn apply_to_a(&mut self, operation: fn(&mut CPU, u8) -> u8) {
+        self.apply_to_register(RegisterTarget::A, operation);
+        self.registers.f.zero = false;
+    }
+
+    fn apply_to_register(&mut self, target: RegisterTarget, operation: fn(&mut CPU, u8) -> u8) {
+        let value = self.get_register_value(target);
+        let new_value = operation(self, value);
+        self.set_register_value(target, new_value);
+    }
+
+    fn apply_to_indirect_hl(&mut self, operation: fn(&mut CPU, u8) -> u8) {
+        let address = self.registers.get_hl();
+        let value = self.read_byte(address);
+        let new_value = operation(self, value);
+        self.write_byte(address, new_value);
+    }
+
+    fn rotate_left_circular(&mut self, value: u8) -> u8 {
+        let new_value = value.rotate_left(1);
+        self.set_shift_flags(new_value, value & 0x80 != 0);
+        new_value
+    }
+
+    fn rotate_right_circular(&mut self, value: u8) -> u8 {
+        let new_value = value.rotate_right(1);
+        self.set_shift_flags(new_value, value & 0x1 != 0);
+        new_value
+    }
+
+    fn rotate_left(&mut self, value: u8) -> u8 {
+        let new_value = value << 1 | self.registers.f.carry as u8;
+        self.set_shift_flags(new_value, value & 0x80 != 0);
+        new_value
+    }
+
+    fn rotate_right(&mut self, value: u8) -> u8 {
+        let new_value = value >> 1 | (self.registers.f.carry as u8) << 7;
+        self.set_shift_flags(new_value, value & 0x1 != 0);
+        new_value
+    }
+
+    fn shift_left_arithmetic(&mut self, value: u8) -> u8 {
+        let new_value = value << 1;
+        self.set_shift_flags(new_value, value & 0x80 != 0);
+        new_value
+    }
+
+    fn shift_right_arithmetic(&mut self, value: u8) -> u8 {
+        let new_value = value >> 1 | (value & 0x80);
+        self.set_shift_flags(new_value, value & 0x1 != 0);
+        new_value
+    }
+
+    fn swap(&mut self, value: u8) -> u8 {
+        let new_value = value.rotate_left(4);
+        self.set_shift_flags(new_value, false);
+        new_value
+    }
+
+    fn shift_right_logical(&mut self, value: u8) -> u8 {
+        let new_value = value >> 1;
+        self.set_shift_flags(new_value, value & 0x1 != 0);
+        new_value
+    }
+
+    fn set_shift_flags(&mut self, new_value: u8, carry: bool) {
+        self.registers.f.zero = new_value == 0;
+        self.registers.f.subtract = false;
+        self.registers.f.half_carry = false;
+        self.registers.f.carry = carry;
+    }
+
+    fn test_bit(&mut self, bit: u8, value: u8) {
+        self.registers.f.zero = value & (1 << bit) == 0;
+        self.registers.f.subtract = false;
+        self.registers.f.half_carry = true;
+    }
+    ///////////////////////////////////////////////////
+}
+
+#[cfg(test)]
+mod test{
+    use strum::IntoEnumIterator;
+    use crate::core::cpu::base::CPU;
+    use crate::core::instructions::definitions::RegisterTarget;
+    use crate::core::model::Model;
+    use crate::core::registers::FlagRegister;
+    use crate::util::Randomizable;
+
+    #[test]
+    fn test_rotate_left_circular(){
+        let mut cpu = CPU::new(Model::Dmg);
+
+        assert_eq!(0x0B, cpu.rotate_left_circular(0x85));
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+
+        assert_eq!(0x0, cpu.rotate_left_circular(0x0));
+        assert_eq!(FlagRegister{
+            zero: true,
+            subtract: false,
+            half_carry: false,
+            carry: false
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_rotate_right_circular(){
+        let mut cpu = CPU::new(Model::Dmg);
+
+        assert_eq!(0x80, cpu.rotate_right_circular(0x01));
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+
+        assert_eq!(0x40, cpu.rotate_right_circular(0x80));
+        assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+    }
+
+    #[test]
+    fn test_rotate_left(){
+        let mut cpu = CPU::new(Model::Dmg);
+
+        assert_eq!(0x0, cpu.rotate_left(0x80));
+        assert_eq!(FlagRegister{
+            zero: true,
+            subtract: false,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+
+        assert_eq!(0x23, cpu.rotate_left(0x11));
+        assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+    }
+
+    #[test]
+    fn test_rotate_right(){
+        let mut cpu = CPU::new(Model::Dmg);
+
+        assert_eq!(0x0, cpu.rotate_right(0x01));
+        assert_eq!(FlagRegister{
+            zero: true,
+            subtract: false,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+
+        assert_eq!(0xC0, cpu.rotate_right(0x80));
+        assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+    }
+
+    #[test]
+    fn test_shift_left_arithmetic(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.f.carry = true;
+
+        assert_eq!(0x02, cpu.shift_left_arithmetic(0x81));
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+
+        assert_eq!(0x0, cpu.shift_left_arithmetic(0x0));
+        assert!(cpu.registers.f.zero);
+        assert!(!cpu.registers.f.carry);
+    }
+
+    #[test]
+    fn test_shift_right_arithmetic(){
+        let mut cpu = CPU::new(Model::Dmg);
+
+        assert_eq!(0xC5, cpu.shift_right_arithmetic(0x8A));
+        assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+
+        assert_eq!(0x0, cpu.shift_right_arithmetic(0x01));
+        assert_eq!(FlagRegister{
+            zero: true,
+            subtract: false,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_swap(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.f = FlagRegister::from(0xF0);
+
+        assert_eq!(0x1F, cpu.swap(0xF1));
+        assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+
+        assert_eq!(0x0, cpu.swap(0x0));
+        assert!(cpu.registers.f.zero);
+    }
+
+    #[test]
+    fn test_shift_right_logical(){
+        let mut cpu = CPU::new(Model::Dmg);
+
+        assert_eq!(0x7F, cpu.shift_right_logical(0xFF));
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+
+        assert_eq!(0x0, cpu.shift_right_logical(0x01));
+        assert!(cpu.registers.f.zero);
+    }
+
+    #[test]
+    fn test_rotate_register(){
+        for target in RegisterTarget::iter() {
+            let mut cpu = CPU::new(Model::Dmg);
+            let value = u8::random();
+            cpu.set_register_value(target, value);
+
+            cpu.rotate_left_circular_register(target);
+
+            assert_eq!(value.rotate_left(1), cpu.get_register_value(target));
+
+            cpu.rotate_right_circular_register(target);
+
+            assert_eq!(value, cpu.get_register_value(target));
+        }
+    }
+
+    #[test]
+    fn test_shift_register(){
+        for target in RegisterTarget::iter() {
+            let mut cpu = CPU::new(Model::Dmg);
+            let value = u8::random();
+            cpu.set_register_value(target, value);
+
+            cpu.shift_left_arithmetic_register(target);
+
+            assert_eq!(value << 1, cpu.get_register_value(target));
+
+            cpu.shift_right_logical_register(target);
+
+            assert_eq!(value & 0x7F, cpu.get_register_value(target));
+        }
+    }
+
+    #[test]
+    fn test_swap_register(){
+        for target in RegisterTarget::iter() {
+            let mut cpu = CPU::new(Model::Dmg);
+            cpu.set_register_value(target, 0xAB);
+
+            cpu.swap_register(target);
+
+            assert_eq!(0xBA, cpu.get_register_value(target));
+        }
+    }
+
+    #[test]
+    fn test_rotate_through_carry_indirect_hl(){
+        let mut cpu = CPU::new(Model::Dmg);
+        let hl_address = 0xC234;
+        cpu.registers.set_hl(hl_address);
+        cpu.bus.write_byte(hl_address, 0x80);
+
+        cpu.rotate_left_indirect_hl();
+
+        assert_eq!(0x0, cpu.bus.read_byte(hl_address));
+        assert!(cpu.registers.f.carry);
+
+        cpu.rotate_right_indirect_hl();
+
+        assert_eq!(0x80, cpu.bus.read_byte(hl_address));
+        assert!(!cpu.registers.f.carry);
+    }
+
+    #[test]
+    fn test_shift_and_swap_indirect_hl(){
+        let mut cpu = CPU::new(Model::Dmg);
+        let hl_address = 0xC234;
+        cpu.registers.set_hl(hl_address);
+        cpu.bus.write_byte(hl_address, 0x81);
+
+        cpu.rotate_left_circular_indirect_hl();
+
+        assert_eq!(0x03, cpu.bus.read_byte(hl_address));
+
+        cpu.rotate_right_circular_indirect_hl();
+
+        assert_eq!(0x81, cpu.bus.read_byte(hl_address));
+
+        cpu.shift_right_arithmetic_indirect_hl();
+
+        assert_eq!(0xC0, cpu.bus.read_byte(hl_address));
+
+        cpu.swap_indirect_hl();
+
+        assert_eq!(0x0C, cpu.bus.read_byte(hl_address));
+
+        cpu.shift_left_arithmetic_indirect_hl();
+
+        assert_eq!(0x18, cpu.bus.read_byte(hl_address));
+
+        cpu.shift_right_logical_indirect_hl();
+
+        assert_eq!(0x0C, cpu.bus.read_byte(hl_address));
+    }
+
+    #[test]
+    fn test_bit_register(){
+        for target in RegisterTarget::iter() {
+            for bit in 0..8 {
+                let mut cpu = CPU::new(Model::Dmg);
+                cpu.registers.f.carry = true;
+                cpu.set_register_value(target, 1 << bit);
+
+                cpu.bit_register(bit, target);
+
+                assert_eq!(FlagRegister{
+                    zero: false,
+                    subtract: false,
+                    half_carry: true,
+                    carry: true
+                }, cpu.registers.f);
+
+                cpu.set_register_value(target, !(1 << bit));
+
+                cpu.bit_register(bit, target);
+
+                assert!(cpu.registers.f.zero);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bit_indirect_hl(){
+        let mut cpu = CPU::new(Model::Dmg);
+        let hl_address = 0xC234;
+        cpu.registers.set_hl(hl_address);
+        cpu.bus.write_byte(hl_address, 0x10);
+
+        cpu.bit_indirect_hl(4);
+
+        assert!(!cpu.registers.f.zero);
+
+        cpu.bit_indirect_hl(3);
+
+        assert!(cpu.registers.f.zero);
+        assert_eq!(0x10, cpu.bus.read_byte(hl_address));
+    }
+
+    #[test]
+    fn test_reset_and_set_register(){
+        for target in RegisterTarget::iter() {
+            for bit in 0..8 {
+                let mut cpu = CPU::new(Model::Dmg);
+                cpu.set_register_value(target, 0xFF);
+
+                cpu.reset_register(bit, target);
+
+                assert_eq!(!(1 << bit), cpu.get_register_value(target));
+
+                cpu.set_register(bit, target);
+
+                assert_eq!(0xFF, cpu.get_register_value(target));
+                assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reset_and_set_indirect_hl(){
+        let mut cpu = CPU::new(Model::Dmg);
+        let hl_address = 0xC234;
+        cpu.registers.set_hl(hl_address);
+
+        cpu.set_indirect_hl(7);
+
+        assert_eq!(0x80, cpu.bus.read_byte(hl_address));
+
+        cpu.set_indirect_hl(0);
+
+        assert_eq!(0x81, cpu.bus.read_byte(hl_address));
+
+        cpu.reset_indirect_hl(7);
+
+        assert_eq!(0x01, cpu.bus.read_byte(hl_address));
+    }
+
+    #[test]
+    fn test_rotate_left_circular_a(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x80;
+
+        cpu.rotate_left_circular_a();
+
+        assert_eq!(0x01, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_rotate_right_circular_a(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x3B;
+
+        cpu.rotate_right_circular_a();
+
+        assert_eq!(0x9D, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_rotate_left_a(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x95;
+        cpu.registers.f.carry = true;
+
+        cpu.rotate_left_a();
+
+        assert_eq!(0x2B, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_rotate_right_a(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.registers.a = 0x81;
+
+        cpu.rotate_right_a();
+
+        assert_eq!(0x40, cpu.registers.a);
+        assert_eq!(FlagRegister{
+            zero: false,
+            subtract: false,
+            half_carry: false,
+            carry: true
+        }, cpu.registers.f);
+    }
+
+    #[test]
+    fn test_accumulator_rotates_reset_zero(){
+        let rotates: [fn(&mut CPU); 4] = [
+            CPU::rotate_left_circular_a,
+            CPU::rotate_right_circular_a,
+            CPU::rotate_left_a,
+            CPU::rotate_right_a
+        ];
+        for rotate in rotates {
+            let mut cpu = CPU::new(Model::Dmg);
+            cpu.registers.a = 0x0;
+            cpu.registers.f.zero = true;
+
+            rotate(&mut cpu);
+
+            assert_eq!(0x0, cpu.registers.a);
+            assert_eq!(FlagRegister::from(0b0), cpu.registers.f);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::core::cpu::base::CPU;
+use crate::util::split_u16;
+
+impl CPU {
+    pub(super) fn disable_interrupts(&mut self) {
+        self.interrupt_master_enable = false;
+        self.interrupt_master_enable_scheduled = false;
+    }
+
+    // IME is only set after the instruction following EI has been executed
+    pub(super) fn enable_interrupts(&mut self) {
+        self.interrupt_master_enable_scheduled = true;
+    }
+
+    pub(super) fn should_dispatch_interrupt(&self) -> bool {
+        self.interrupt_master_enable && self.bus.interrupts.pending().is_some()
+    }
+
+    // Takes 5 M-cycles: two wait states, the two pc pushes and the jump to the vector.
+    // The interrupt to serve is only picked after the msb has been pushed, so a push that
+    // overwrites IE can cancel the dispatch, which then jumps to 0x0000.
+    pub(super) fn dispatch_interrupt(&mut self) {
+        self.interrupt_master_enable = false;
+        // An interrupt right after a HALT bug (EI; HALT) returns to HALT itself
+        if self.halt_bug {
+            self.halt_bug = false;
+            self.program_counter = self.program_counter.wrapping_sub(1);
+        }
+        let (msb_pc, lsb_pc) = split_u16(self.program_counter);
+        self.tick();
+        self.tick();
+        self.stack_pointer = self.stack_pointer.wrapping_sub(1);
+        self.write_byte(self.stack_pointer, msb_pc);
+        let interrupt = self.bus.interrupts.pending();
+        self.stack_pointer = self.stack_pointer.wrapping_sub(1);
+        self.write_byte(self.stack_pointer, lsb_pc);
+        self.program_counter = match interrupt {
+            Some(interrupt) => {
+                self.bus.interrupts.acknowledge(interrupt);
+                interrupt.vector()
+            }
+            None => 0x0000
+        };
+        self.tick();
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use strum::IntoEnumIterator;
+    use crate::core::cpu::base::CPU;
+    use crate::core::interrupts::Interrupt;
+    use crate::core::model::Model;
+
+    fn cpu_with_interrupts_enabled() -> CPU {
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC000;
+        cpu.stack_pointer = 0xDFF0;
+        cpu.interrupt_master_enable = true;
+        cpu.bus.write_byte(0xFFFF, 0x1F);
+        cpu
+    }
+
+    #[test]
+    fn test_disable_interrupts(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.interrupt_master_enable = true;
+        cpu.interrupt_master_enable_scheduled = true;
+
+        cpu.disable_interrupts();
+
+        assert!(!cpu.interrupt_master_enable);
+        assert!(!cpu.interrupt_master_enable_scheduled);
+    }
+
+    #[test]
+    fn test_enable_interrupts_is_delayed(){
+        let mut cpu = CPU::new(Model::Dmg);
+
+        cpu.enable_interrupts();
+
+        assert!(!cpu.interrupt_master_enable);
+        assert!(cpu.interrupt_master_enable_scheduled);
+    }
+
+    #[test]
+    fn test_dispatch_interrupt(){
+        for interrupt in Interrupt::iter() {
+            let mut cpu = cpu_with_interrupts_enabled();
+            cpu.bus.request_interrupt(interrupt);
+
+            cpu.dispatch_interrupt();
+
+            assert_eq!(interrupt.vector(), cpu.program_counter);
+            assert_eq!(0xDFEE, cpu.stack_pointer);
+            assert_eq!(0xC000, cpu.bus.read_word(0xDFEE));
+            assert_eq!(0xE0, cpu.bus.read_byte(0xFF0F));
+            assert!(!cpu.interrupt_master_enable);
+            assert_eq!(5, cpu.cycles);
+        }
+    }
+
+    #[test]
+    fn test_dispatch_only_acknowledges_served_interrupt(){
+        let mut cpu = cpu_with_interrupts_enabled();
+        cpu.bus.request_interrupt(Interrupt::Joypad);
+        cpu.bus.request_interrupt(Interrupt::Timer);
+
+        cpu.dispatch_interrupt();
+
+        assert_eq!(0x50, cpu.program_counter);
+        assert_eq!(Some(Interrupt::Joypad), cpu.bus.interrupts.pending());
+    }
+
+    #[test]
+    fn test_dispatch_cancelled_by_ie_push(){
+        let mut cpu = cpu_with_interrupts_enabled();
+        // pushing the msb of pc (0x02) to 0xFFFF disables the requested timer interrupt
+        cpu.program_counter = 0x0234;
+        cpu.stack_pointer = 0x0000;
+        cpu.bus.request_interrupt(Interrupt::Timer);
+
+        cpu.dispatch_interrupt();
+
+        assert_eq!(0x0000, cpu.program_counter);
+        assert_eq!(0x02, cpu.bus.read_byte(0xFFFF));
+        assert_eq!(0xE4, cpu.bus.read_byte(0xFF0F));
+    }
+}
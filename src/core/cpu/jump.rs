@@ -1,15 +1,9 @@
 use crate::core::cpu::base::CPU;
 use crate::core::instructions::definitions::JumpCondition;
-use crate::util::join_u8;
 
 impl CPU{
     pub (super) fn jump_conditional_to_nn(&mut self, jump_condition: JumpCondition){
-        let should_jump = match jump_condition {
-            JumpCondition::NotZero => !self.registers.f.zero,
-            JumpCondition::Zero => self.registers.f.zero,
-            JumpCondition::NotCarry => !self.registers.f.carry,
-            JumpCondition::Carry => self.registers.f.carry
-        };
+        let should_jump = self.is_condition_satisfied(jump_condition);
         self.jump(should_jump)
     }
 
@@ -17,20 +11,88 @@ impl CPU{
         self.jump(true);
     }
 
+    pub (super) fn jump_to_hl(&mut self) {
+        self.program_counter = self.registers.get_hl();
+    }
+
+    pub (super) fn jump_relative(&mut self) {
+        self.jump_relative_if(true);
+    }
+
+    pub (super) fn jump_conditional_relative(&mut self, jump_condition: JumpCondition) {
+        let should_jump = self.is_condition_satisfied(jump_condition);
+        self.jump_relative_if(should_jump);
+    }
+
+    pub (super) fn call_nn(&mut self) {
+        self.call(true);
+    }
+
+    pub (super) fn call_conditional_nn(&mut self, jump_condition: JumpCondition) {
+        let should_call = self.is_condition_satisfied(jump_condition);
+        self.call(should_call);
+    }
+
+    pub (super) fn return_from_call(&mut self) {
+        let address = self.pop_word();
+        self.tick();
+        self.program_counter = address;
+    }
+
+    // Evaluating the condition takes an extra cycle, whether the return happens or not
+    pub (super) fn return_conditional(&mut self, jump_condition: JumpCondition) {
+        self.tick();
+        if self.is_condition_satisfied(jump_condition) {
+            self.return_from_call();
+        }
+    }
+
+    pub (super) fn return_interrupt(&mut self) {
+        self.return_from_call();
+        self.interrupt_master_enable = true;
+    }
+
+    pub (super) fn restart(&mut self, address: u8) {
+        let program_counter = self.program_counter;
+        self.push_word(program_counter);
+        self.program_counter = address as u16;
+    }
+
+    fn is_condition_satisfied(&self, jump_condition: JumpCondition) -> bool {
+        match jump_condition {
+            JumpCondition::NotZero => !self.registers.f.zero,
+            JumpCondition::Zero => self.registers.f.zero,
+            JumpCondition::NotCarry => !self.registers.f.carry,
+            JumpCondition::Carry => self.registers.f.carry
+        }
+    }
+
+    // The address is always read, the extra cycle to load it into pc is only spent if the jump is taken
     fn jump(&mut self, should_jump: bool) {
+        let address = self.read_word_and_increment_pc();
         if should_jump {
-            self.jump_to_pc_pointed_address();
-        } else {
-            let new_pc = self.program_counter.wrapping_add(2);
-            self.program_counter = new_pc;
+            self.tick();
+            self.program_counter = address;
         }
     }
 
-    fn jump_to_pc_pointed_address(&mut self) {
-        let lsb_address = self.bus.read_byte(self.program_counter);
-        let msb_address = self.bus.read_byte(self.program_counter.wrapping_add(1));
-        let address = join_u8(msb_address, lsb_address);
-        self.program_counter = address;
+    // The offset is relative to the address of the instruction following JR
+    fn jump_relative_if(&mut self, should_jump: bool) {
+        let offset = self.read_byte_and_increment_pc() as i8;
+        if should_jump {
+            self.tick();
+            self.program_counter = self.program_counter.wrapping_add_signed(offset as i16);
+        }
+    }
+
+    // The pushed return address is the one of the instruction following CALL
+    fn call(&mut self, should_call: bool) {
+        let address = self.read_word_and_increment_pc();
+        if should_call {
+            let return_address = self.program_counter;
+            self.push_word(return_address);
+            self.program_counter = address;
+        }
     }
 }
 
@@ -39,13 +101,15 @@ impl CPU{
 mod test{
     use crate::core::cpu::base::CPU;
     use crate::core::instructions::definitions::JumpCondition::{Carry, NotCarry, NotZero, Zero};
+    use crate::core::model::Model;
+    use crate::util::Randomizable;
 
     #[test]
     fn test_jump_not_zero(){
-        let mut cpu = CPU::new();
-        cpu.program_counter = 0x1234;
-        cpu.bus.write_byte(0x1234, 0x56);
-        cpu.bus.write_byte(0x1235, 0x78);
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC234;
+        cpu.bus.write_byte(0xC234, 0x56);
+        cpu.bus.write_byte(0xC235, 0x78);
         cpu.registers.f.zero = false;
 
         cpu.jump_conditional_to_nn(NotZero);
@@ -62,10 +126,10 @@ mod test{
 
     #[test]
     fn test_jump_zero(){
-        let mut cpu = CPU::new();
-        cpu.program_counter = 0x1234;
-        cpu.bus.write_byte(0x1234, 0x56);
-        cpu.bus.write_byte(0x1235, 0x78);
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC234;
+        cpu.bus.write_byte(0xC234, 0x56);
+        cpu.bus.write_byte(0xC235, 0x78);
         cpu.registers.f.zero = true;
 
         cpu.jump_conditional_to_nn(Zero);
@@ -82,10 +146,10 @@ mod test{
 
     #[test]
     fn test_jump_carry(){
-        let mut cpu = CPU::new();
-        cpu.program_counter = 0x1234;
-        cpu.bus.write_byte(0x1234, 0x56);
-        cpu.bus.write_byte(0x1235, 0x78);
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC234;
+        cpu.bus.write_byte(0xC234, 0x56);
+        cpu.bus.write_byte(0xC235, 0x78);
         cpu.registers.f.carry = true;
 
         cpu.jump_conditional_to_nn(Carry);
@@ -102,10 +166,10 @@ mod test{
 
     #[test]
     fn test_jump_not_carry(){
-        let mut cpu = CPU::new();
-        cpu.program_counter = 0x1234;
-        cpu.bus.write_byte(0x1234, 0x56);
-        cpu.bus.write_byte(0x1235, 0x78);
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC234;
+        cpu.bus.write_byte(0xC234, 0x56);
+        cpu.bus.write_byte(0xC235, 0x78);
         cpu.registers.f.carry = false;
 
         cpu.jump_conditional_to_nn(NotCarry);
@@ -122,14 +186,202 @@ mod test{
 
     #[test]
     fn test_jump(){
-        let mut cpu = CPU::new();
-        cpu.program_counter = 0x1234;
-        cpu.bus.write_byte(0x1234, 0x56);
-        cpu.bus.write_byte(0x1235, 0x78);
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC234;
+        cpu.bus.write_byte(0xC234, 0x56);
+        cpu.bus.write_byte(0xC235, 0x78);
         cpu.registers.f.carry = true;
 
         cpu.jump_to_nn();
 
         assert_eq!(0x7856, cpu.program_counter);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_jump_to_hl(){
+        let mut cpu = CPU::new(Model::Dmg);
+        let address = u16::random();
+        cpu.registers.set_hl(address);
+
+        cpu.jump_to_hl();
+
+        assert_eq!(address, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_jump_relative_forward(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC234;
+        cpu.bus.write_byte(0xC234, 0x10);
+
+        cpu.jump_relative();
+
+        assert_eq!(0xC245, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_jump_relative_backward(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC234;
+        // JR -2 jumps back to the JR opcode itself
+        cpu.bus.write_byte(0xC234, 0xFE);
+
+        cpu.jump_relative();
+
+        assert_eq!(0xC233, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_jump_relative_wraps(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xFFFE;
+        cpu.bus.write_byte(0xFFFE, 0x05);
+
+        cpu.jump_relative();
+
+        assert_eq!(0x0004, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_jump_conditional_relative(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC234;
+        cpu.bus.write_byte(0xC234, 0x10);
+        cpu.bus.write_byte(0xC245, 0x80);
+        cpu.registers.f.zero = true;
+
+        cpu.jump_conditional_relative(Zero);
+
+        assert_eq!(0xC245, cpu.program_counter);
+
+        cpu.jump_conditional_relative(NotZero);
+
+        assert_eq!(0xC246, cpu.program_counter);
+
+        cpu.program_counter = 0xC245;
+        cpu.registers.f.carry = true;
+
+        cpu.jump_conditional_relative(Carry);
+
+        assert_eq!(0xC1C6, cpu.program_counter);
+
+        cpu.program_counter = 0xC234;
+
+        cpu.jump_conditional_relative(NotCarry);
+
+        assert_eq!(0xC235, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_call_nn(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC234;
+        cpu.stack_pointer = 0xFFFE;
+        cpu.bus.write_byte(0xC234, 0x56);
+        cpu.bus.write_byte(0xC235, 0x78);
+
+        cpu.call_nn();
+
+        assert_eq!(0x7856, cpu.program_counter);
+        assert_eq!(0xFFFC, cpu.stack_pointer);
+        assert_eq!(0xC236, cpu.bus.read_word(0xFFFC));
+    }
+
+    #[test]
+    fn test_call_conditional_nn(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC234;
+        cpu.stack_pointer = 0xFFFE;
+        cpu.bus.write_byte(0xC234, 0x56);
+        cpu.bus.write_byte(0xC235, 0x78);
+        cpu.registers.f.carry = true;
+
+        cpu.call_conditional_nn(NotCarry);
+
+        assert_eq!(0xC236, cpu.program_counter);
+        assert_eq!(0xFFFE, cpu.stack_pointer);
+
+        cpu.program_counter = 0xC234;
+
+        cpu.call_conditional_nn(Carry);
+
+        assert_eq!(0x7856, cpu.program_counter);
+        assert_eq!(0xFFFC, cpu.stack_pointer);
+        assert_eq!(0xC236, cpu.bus.read_word(0xFFFC));
+    }
+
+    #[test]
+    fn test_return_from_call(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.stack_pointer = 0xFFFC;
+        cpu.bus.write_word(0xFFFC, 0xC236);
+
+        cpu.return_from_call();
+
+        assert_eq!(0xC236, cpu.program_counter);
+        assert_eq!(0xFFFE, cpu.stack_pointer);
+    }
+
+    #[test]
+    fn test_call_then_return(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0xC234;
+        cpu.stack_pointer = 0xFFFE;
+        cpu.bus.write_byte(0xC234, 0x56);
+        cpu.bus.write_byte(0xC235, 0x78);
+
+        cpu.call_nn();
+        cpu.return_from_call();
+
+        assert_eq!(0xC236, cpu.program_counter);
+        assert_eq!(0xFFFE, cpu.stack_pointer);
+    }
+
+    #[test]
+    fn test_return_conditional(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = 0x1000;
+        cpu.stack_pointer = 0xFFFC;
+        cpu.bus.write_word(0xFFFC, 0xC236);
+        cpu.registers.f.zero = false;
+
+        cpu.return_conditional(Zero);
+
+        assert_eq!(0x1000, cpu.program_counter);
+        assert_eq!(0xFFFC, cpu.stack_pointer);
+
+        cpu.return_conditional(NotZero);
+
+        assert_eq!(0xC236, cpu.program_counter);
+        assert_eq!(0xFFFE, cpu.stack_pointer);
+    }
+
+    #[test]
+    fn test_return_interrupt(){
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.stack_pointer = 0xFFFC;
+        cpu.bus.write_word(0xFFFC, 0xC236);
+        cpu.interrupt_master_enable = false;
+
+        cpu.return_interrupt();
+
+        assert_eq!(0xC236, cpu.program_counter);
+        assert_eq!(0xFFFE, cpu.stack_pointer);
+        assert!(cpu.interrupt_master_enable);
+    }
+
+    #[test]
+    fn test_restart(){
+        for address in [0x00, 0x08, 0x10, 0x18, 0x20, 0x28, 0x30, 0x38] {
+            let mut cpu = CPU::new(Model::Dmg);
+            cpu.program_counter = 0xC235;
+            cpu.stack_pointer = 0xFFFE;
+
+            cpu.restart(address);
+
+            assert_eq!(address as u16, cpu.program_counter);
+            assert_eq!(0xFFFC, cpu.stack_pointer);
+            assert_eq!(0xC235, cpu.bus.read_word(0xFFFC));
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::core::cpu::base::CPU;
 use crate::core::instructions::definitions::{PushPopTarget, RegisterTarget16};
+use crate::util::split_u16;
 
 impl CPU {
     pub(super) fn load_register16_nn(&mut self, target: RegisterTarget16) {
@@ -10,26 +11,40 @@ impl CPU {
     pub(super) fn load_nn_from_stack_pointer(&mut self){
         let nn_address = self.read_word_and_increment_pc();
         let stack_pointer = self.stack_pointer;
-        self.bus.write_word(nn_address, stack_pointer);
+        self.write_word(nn_address, stack_pointer);
     }
 
     pub(super) fn load_stack_pointer_from_hl(&mut self){
         let hl_value = self.registers.get_hl();
+        self.tick();
         self.stack_pointer = hl_value;
     }
 
     pub(super) fn push_from_register(&mut self, source: PushPopTarget) {
-        let new_stack_pointer = self.stack_pointer.wrapping_sub(2);
         let value = self.get_push_pop_target_value(source);
-        self.bus.write_word(new_stack_pointer, value);
-        self.stack_pointer = new_stack_pointer;
+        self.push_word(value);
     }
 
     pub(super) fn pop_into_register(&mut self, target: PushPopTarget) {
-        let stack_pointer = self.stack_pointer;
-        let value = self.bus.read_word(stack_pointer);
+        let value = self.pop_word();
         self.set_push_pop_target_value(target, value);
+    }
+
+    // SP is decremented during an internal cycle, then the msb is written before the lsb
+    pub(super) fn push_word(&mut self, value: u16) {
+        let (msb_value, lsb_value) = split_u16(value);
+        self.tick();
+        self.stack_pointer = self.stack_pointer.wrapping_sub(1);
+        self.write_byte(self.stack_pointer, msb_value);
+        self.stack_pointer = self.stack_pointer.wrapping_sub(1);
+        self.write_byte(self.stack_pointer, lsb_value);
+    }
+
+    pub(super) fn pop_word(&mut self) -> u16 {
+        let stack_pointer = self.stack_pointer;
+        let value = self.read_word(stack_pointer);
         self.stack_pointer = stack_pointer.wrapping_add(2);
+        value
     }
 
 }
@@ -40,16 +55,17 @@ mod test{
     use strum::IntoEnumIterator;
     use crate::core::cpu::base::CPU;
     use crate::core::instructions::definitions::{PushPopTarget, RegisterTarget16};
+    use crate::core::model::Model;
     use crate::core::registers::AF_BIT_MASK;
-    use crate::util::{Randomizable, split_u16};
+    use crate::util::{random_wram_address, Randomizable, split_u16};
 
     #[test]
     fn test_load_register16_nn(){
         for receiver in RegisterTarget16::iter() {
-            let mut cpu = CPU::new();
+            let mut cpu = CPU::new(Model::Dmg);
             let value = u16::random();
             let (msb_value, lsb_value) = split_u16(value);
-            let pc = u16::random();
+            let pc = random_wram_address();
             cpu.program_counter = pc;
             cpu.bus.write_byte(pc, lsb_value);
             cpu.bus.write_byte(pc.wrapping_add(1), msb_value);
@@ -63,10 +79,10 @@ mod test{
 
     #[test]
     fn test_load_nn_from_stack_pointer(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         let sp_address = u16::random();
-        let nn = u16::random();
-        let nn_address = u16::random();
+        let nn = random_wram_address();
+        let nn_address = random_wram_address();
 
         cpu.bus.write_word(nn_address, nn);
         cpu.program_counter = nn_address;
@@ -80,7 +96,7 @@ mod test{
 
     #[test]
     fn test_load_stack_pointer_from_hl(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         let value = u16::random();
         cpu.registers.set_hl(value);
 
@@ -92,10 +108,10 @@ mod test{
     #[test]
     fn test_push_from_register(){
         for source in PushPopTarget::iter() {
-            let mut cpu = CPU::new();
+            let mut cpu = CPU::new(Model::Dmg);
             let mut value = u16::random();
             if source == PushPopTarget::AF {
-                value = value & AF_BIT_MASK;
+                value &= AF_BIT_MASK;
             }
             let old_stack_pointer = cpu.stack_pointer;
             cpu.set_push_pop_target_value(source, value);
@@ -110,12 +126,12 @@ mod test{
     #[test]
     fn test_pop_into_register(){
         for target in PushPopTarget::iter() {
-            let mut cpu = CPU::new();
+            let mut cpu = CPU::new(Model::Dmg);
             let mut value = u16::random();
-            let sp = u16::random();
+            let sp = random_wram_address();
             cpu.stack_pointer = sp;
             if target == PushPopTarget::AF {
-                value = value & AF_BIT_MASK;
+                value &= AF_BIT_MASK;
             }
             cpu.bus.write_word(sp, value);
 
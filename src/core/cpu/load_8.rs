@@ -13,84 +13,84 @@ impl CPU{
     }
     pub (super) fn load_register_indirect_hl(&mut self, target: RegisterTarget) {
         let address = self.registers.get_hl();
-        let value = self.bus.read_byte(address);
+        let value = self.read_byte(address);
         self.set_register_value(target, value);
     }
     pub (super) fn load_indirect_hl_register(&mut self, source: RegisterTarget) {
         let value = self.get_register_value(source);
         let address = self.registers.get_hl();
-        self.bus.write_byte(address, value);
+        self.write_byte(address, value);
     }
 
     pub (super) fn load_indirect_hl_n(&mut self) {
         let address = self.registers.get_hl();
         let n = self.read_byte_and_increment_pc();
-        self.bus.write_byte(address, n);
+        self.write_byte(address, n);
     }
 
     pub (super) fn load_a_bc(&mut self) {
         let address = self.registers.get_bc();
-        let value = self.bus.read_byte(address);
+        let value = self.read_byte(address);
         self.registers.a = value;
     }
 
     pub (super) fn load_a_de(&mut self) {
         let address = self.registers.get_de();
-        let value = self.bus.read_byte(address);
+        let value = self.read_byte(address);
         self.registers.a = value;
     }
 
     pub (super) fn load_bc_a(&mut self) {
         let address = self.registers.get_bc();
         let value = self.registers.a;
-        self.bus.write_byte(address, value);
+        self.write_byte(address, value);
     }
 
     pub (super) fn load_de_a(&mut self){
         let address = self.registers.get_de();
         let value = self.registers.a;
-        self.bus.write_byte(address, value);
+        self.write_byte(address, value);
     }
 
     pub (super) fn load_a_nn(&mut self){
         let address = self.read_word_and_increment_pc();
-        let value = self.bus.read_byte(address);
+        let value = self.read_byte(address);
         self.registers.a = value;
     }
 
     pub (super) fn load_nn_a(&mut self){
         let value = self.registers.a;
         let address = self.read_word_and_increment_pc();
-        self.bus.write_byte(address, value);
+        self.write_byte(address, value);
     }
 
     pub (super) fn load_half_a_c(&mut self){
         let address = get_absolute_address_from_lsb(self.registers.c);
-        let value = self.bus.read_byte(address);
+        let value = self.read_byte(address);
         self.registers.a = value;
     }
 
     pub (super) fn load_half_c_a(&mut self){
         let address = get_absolute_address_from_lsb(self.registers.c);
         let value = self.registers.a;
-        self.bus.write_byte(address, value);
+        self.write_byte(address, value);
     }
 
     pub (super) fn load_half_a_n(&mut self){
         let lsb_address = self.read_byte_and_increment_pc();
-        let value = self.bus.read_byte(get_absolute_address_from_lsb(lsb_address));
+        let value = self.read_byte(get_absolute_address_from_lsb(lsb_address));
         self.registers.a = value;
     }
 
     pub (super) fn load_half_n_a(&mut self){
         let lsb_address = self.read_byte_and_increment_pc();
         let value = self.registers.a;
-        self.bus.write_byte(get_absolute_address_from_lsb(lsb_address), value);
+        self.write_byte(get_absolute_address_from_lsb(lsb_address), value);
     }
 
     pub (super) fn load_a_indirect_hl_decrement(&mut self){
         let address = self.registers.get_hl();
-        let value = self.bus.read_byte(address);
+        let value = self.read_byte(address);
 
         self.registers.a = value;
         self.registers.set_hl(address.wrapping_sub(1));
@@ -100,13 +100,13 @@ impl CPU{
         let address = self.registers.get_hl();
         let value = self.registers.a;
 
-        self.bus.write_byte(address, value);
+        self.write_byte(address, value);
         self.registers.set_hl(address.wrapping_sub(1));
     }
 
     pub (super) fn load_a_indirect_hl_increment(&mut self){
         let address = self.registers.get_hl();
-        let value = self.bus.read_byte(address);
+        let value = self.read_byte(address);
 
         self.registers.a = value;
         self.registers.set_hl(address.wrapping_add(1));
@@ -116,14 +116,14 @@ impl CPU{
         let address = self.registers.get_hl();
         let value = self.registers.a;
 
-        self.bus.write_byte(address, value);
+        self.write_byte(address, value);
         self.registers.set_hl(address.wrapping_add(1));
     }
 
 }
 
 fn get_absolute_address_from_lsb(lsb_address: u8) -> u16{
-    return join_u8(0xFF, lsb_address);
+    join_u8(0xFF, lsb_address)
 }
 
 #[cfg(test)]
@@ -132,7 +132,8 @@ mod test{
     use crate::core::cpu::base::CPU;
     use crate::core::cpu::load_8::get_absolute_address_from_lsb;
     use crate::core::instructions::definitions::RegisterTarget;
-    use crate::util::{join_u8, Randomizable, split_u16};
+    use crate::core::model::Model;
+    use crate::util::{join_u8, random_wram_address, Randomizable, split_u16};
 
     #[test]
     fn test_get_absolute_address_from_lsb(){
@@ -141,7 +142,7 @@ mod test{
 
     #[test]
     fn test_ld_hl_inc_a(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         let hl_address = u16::MAX;
         let val = 0x3;
         cpu.registers.set_hl(hl_address);
@@ -155,7 +156,7 @@ mod test{
 
     #[test]
     fn test_ld_a_hl_inc(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         let hl_address = u16::MAX;
         cpu.registers.set_hl(hl_address);
 
@@ -169,7 +170,7 @@ mod test{
 
     #[test]
     fn test_ld_hl_dec_a(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         let hl_address = u16::MAX;
         let val = 0x3;
         cpu.registers.set_hl(hl_address);
@@ -183,7 +184,7 @@ mod test{
 
     #[test]
     fn test_ld_a_hl_dec(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         let hl_address = 0;
         cpu.registers.set_hl(hl_address);
 
@@ -197,10 +198,10 @@ mod test{
 
     #[test]
     fn test_ld_h_n_a(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         let val = 0x12;
         let lsb_address = 0x30;
-        let pc_address = 0x1234;
+        let pc_address = 0xC234;
         cpu.registers.a = val;
         cpu.program_counter = pc_address;
         cpu.bus.write_byte(pc_address, lsb_address);
@@ -213,9 +214,11 @@ mod test{
 
     #[test]
     fn test_ld_h_a_n(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         let n_address = 0x12;
         let full_address = join_u8(0xFF, n_address);
+        cpu.program_counter = 0xC000;
+        cpu.bus.write_byte(0xC000, n_address);
 
         cpu.load_half_a_n();
 
@@ -226,7 +229,7 @@ mod test{
 
     #[test]
     fn test_ld_h_c_a(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         let val = 0x12;
         let lsb_address = 0x30;
         cpu.registers.c = lsb_address;
@@ -240,7 +243,7 @@ mod test{
 
     #[test]
     fn test_ld_h_a_c(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         let c_address = 0x12;
         cpu.registers.c = c_address;
         let full_address = join_u8(0xFF, c_address);
@@ -254,8 +257,11 @@ mod test{
 
     #[test]
     fn test_ld_a_nn(){
-        let mut cpu = CPU::new();
-        let address = 0x12;
+        let mut cpu = CPU::new(Model::Dmg);
+        let address = 0xD012;
+        cpu.program_counter = 0xC000;
+        cpu.bus.write_word(0xC000, address);
+        cpu.bus.write_byte(address, 0x34);
 
         cpu.load_a_nn();
 
@@ -266,14 +272,14 @@ mod test{
 
     #[test]
     fn test_ld_nn_a(){
-        let mut cpu = CPU::new();
-        let lsb_address_pointer = 0x1234;
-        let target_address = 0x5678;
+        let mut cpu = CPU::new(Model::Dmg);
+        let lsb_address_pointer = 0xC234;
+        let target_address = 0xD678;
         let val = 0x3;
         cpu.program_counter = lsb_address_pointer;
         cpu.registers.a = val;
         cpu.bus.write_byte(lsb_address_pointer, 0x78);
-        cpu.bus.write_byte(lsb_address_pointer.wrapping_add(1), 0x56);
+        cpu.bus.write_byte(lsb_address_pointer.wrapping_add(1), 0xD6);
 
         cpu.load_nn_a();
 
@@ -283,8 +289,8 @@ mod test{
 
     #[test]
     fn test_ld_bc_a(){
-        let mut cpu = CPU::new();
-        let target_address = 0x5678;
+        let mut cpu = CPU::new(Model::Dmg);
+        let target_address = 0xD678;
         let val = 0x3;
         cpu.registers.a = val;
         cpu.registers.set_bc(target_address);
@@ -296,8 +302,8 @@ mod test{
 
     #[test]
     fn test_ld_de_a(){
-        let mut cpu = CPU::new();
-        let target_address = 0x5678;
+        let mut cpu = CPU::new(Model::Dmg);
+        let target_address = 0xD678;
         let val = 0x3;
         cpu.registers.a = val;
         cpu.registers.set_de(target_address);
@@ -309,7 +315,7 @@ mod test{
 
     #[test]
     fn test_ld_a_de(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
 
         cpu.load_a_de();
 
@@ -321,7 +327,7 @@ mod test{
 
     #[test]
     fn test_ld_a_bc(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
 
         cpu.load_a_bc();
 
@@ -333,9 +339,9 @@ mod test{
 
     #[test]
     fn test_ld_hl_n(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         let n = u8::random();
-        let pc_address = u16::random();
+        let pc_address = random_wram_address();
         let hl_address = pc_address.wrapping_add(0x5);
         cpu.bus.write_byte(pc_address, n);
         cpu.program_counter = pc_address;
@@ -350,9 +356,9 @@ mod test{
     #[test]
     fn test_ld_r_hl(){
         for receiver in RegisterTarget::iter() {
-            let mut cpu = CPU::new();
+            let mut cpu = CPU::new(Model::Dmg);
             let value = u8::random();
-            let address = u16::random();
+            let address = random_wram_address();
             cpu.bus.write_byte(address, value);
             cpu.registers.set_hl(address);
 
@@ -365,8 +371,8 @@ mod test{
     #[test]
     fn test_ld_hl_r(){
         for source in RegisterTarget::iter() {
-            let mut cpu = CPU::new();
-            let address = u16::random();
+            let mut cpu = CPU::new(Model::Dmg);
+            let address = random_wram_address();
             let value = match source {
                 RegisterTarget::H => split_u16(address).0 ,
                 RegisterTarget::L => split_u16(address).1 ,
@@ -384,9 +390,9 @@ mod test{
     #[test]
     fn test_ld_r_n(){
         for receiver in RegisterTarget::iter() {
-            let mut cpu = CPU::new();
+            let mut cpu = CPU::new(Model::Dmg);
             let value = u8::random();
-            let pc = u16::random();
+            let pc = random_wram_address();
             cpu.program_counter = pc;
             cpu.bus.write_byte(pc, value);
 
@@ -401,7 +407,7 @@ mod test{
     fn test_ld_r_r(){
         for source in RegisterTarget::iter(){
             for receiver in RegisterTarget::iter() {
-                let mut cpu = CPU::new();
+                let mut cpu = CPU::new(Model::Dmg);
                 *cpu.get_register_pointer(source) = 0x1;
 
                 cpu.load_register_register(source, receiver);
@@ -0,0 +1,263 @@
+use crate::core::cpu::base::{CPU, CpuError, CpuState};
+
+// Roughly 8200 T-cycles, during which the CPU is paused while the clock switches
+pub(super) const SPEED_SWITCH_CYCLES: u16 = 2050;
+
+impl CPU {
+    // With IME reset and an interrupt already pending, HALT is skipped and the next opcode
+    // fetch fails to increment pc (the DMG "HALT bug")
+    pub(super) fn halt(&mut self) {
+        if !self.interrupt_master_enable && self.bus.interrupts.pending().is_some() {
+            self.halt_bug = true;
+        } else {
+            self.state = CpuState::Halted;
+        }
+    }
+
+    // STOP is followed by a padding byte, which gets skipped, and resets DIV. On CGB, if KEY1
+    // was armed, it switches speed instead of stopping.
+    pub(super) fn stop(&mut self) {
+        self.program_counter = self.program_counter.wrapping_add(1);
+        self.bus.timer.reset_div();
+        if self.bus.cgb_mode && self.bus.speed_switch_armed {
+            self.bus.switch_speed();
+            self.state = CpuState::SwitchingSpeed { remaining_cycles: SPEED_SWITCH_CYCLES };
+        } else {
+            self.state = CpuState::Stopped;
+        }
+    }
+
+    // HALT ends as soon as an interrupt is pending, even if IME is reset
+    pub(super) fn step_halted(&mut self) -> Result<(), CpuError> {
+        if self.bus.interrupts.pending().is_some() {
+            self.state = CpuState::Running;
+            self.step_running()
+        } else {
+            self.tick();
+            Ok(())
+        }
+    }
+
+    pub(super) fn step_stopped(&mut self) -> Result<(), CpuError> {
+        if self.bus.joypad.has_selected_input() {
+            self.state = CpuState::Running;
+            self.step_running()
+        } else {
+            self.tick();
+            Ok(())
+        }
+    }
+
+    pub(super) fn step_switching_speed(&mut self, remaining_cycles: u16) {
+        self.tick();
+        self.state = match remaining_cycles {
+            0 | 1 => CpuState::Running,
+            _ => CpuState::SwitchingSpeed { remaining_cycles: remaining_cycles - 1 }
+        };
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::cpu::base::{CPU, CpuState};
+    use crate::core::cpu::low_power::SPEED_SWITCH_CYCLES;
+    use crate::core::interrupts::Interrupt;
+    use crate::core::joypad::Button;
+    use crate::core::model::Model;
+
+    const PROGRAM_ADDRESS: u16 = 0xC000;
+
+    fn cpu_with_program(program: &[u8]) -> CPU {
+        let mut cpu = CPU::new(Model::Dmg);
+        cpu.program_counter = PROGRAM_ADDRESS;
+        cpu.stack_pointer = 0xDFF0;
+        for (offset, byte) in program.iter().enumerate() {
+            cpu.bus.write_byte(PROGRAM_ADDRESS + offset as u16, *byte);
+        }
+        cpu
+    }
+
+    #[test]
+    fn test_halt_waits_for_interrupt(){
+        // HALT; INC A
+        let mut cpu = cpu_with_program(&[0x76, 0x3C]);
+        cpu.bus.write_byte(0xFFFF, 0x04);
+
+        cpu.step().unwrap();
+
+        assert_eq!(CpuState::Halted, cpu.state);
+
+        for _ in 0..10 {
+            assert_eq!(1, cpu.step().unwrap());
+        }
+
+        assert_eq!(PROGRAM_ADDRESS + 1, cpu.program_counter);
+        assert_eq!(0x0, cpu.registers.a);
+
+        cpu.bus.request_interrupt(Interrupt::Timer);
+        cpu.step().unwrap();
+
+        // IME is reset, so execution simply resumes after HALT
+        assert_eq!(CpuState::Running, cpu.state);
+        assert_eq!(0x1, cpu.registers.a);
+        assert_eq!(PROGRAM_ADDRESS + 2, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_halt_ignores_disabled_interrupt(){
+        let mut cpu = cpu_with_program(&[0x76]);
+
+        cpu.step().unwrap();
+        cpu.bus.request_interrupt(Interrupt::Timer);
+        cpu.step().unwrap();
+
+        assert_eq!(CpuState::Halted, cpu.state);
+    }
+
+    #[test]
+    fn test_halt_wakes_into_dispatch(){
+        let mut cpu = cpu_with_program(&[0x76, 0x00]);
+        cpu.interrupt_master_enable = true;
+        cpu.bus.write_byte(0xFFFF, 0x01);
+
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        cpu.bus.request_interrupt(Interrupt::VBlank);
+        cpu.step().unwrap();
+
+        assert_eq!(CpuState::Running, cpu.state);
+        assert_eq!(0x40, cpu.program_counter);
+        assert_eq!(PROGRAM_ADDRESS + 1, cpu.bus.read_word(cpu.stack_pointer));
+    }
+
+    #[test]
+    fn test_halt_bug(){
+        // HALT; INC A; NOP
+        let mut cpu = cpu_with_program(&[0x76, 0x3C, 0x00]);
+        cpu.bus.write_byte(0xFFFF, 0x04);
+        cpu.bus.request_interrupt(Interrupt::Timer);
+
+        cpu.step().unwrap();
+
+        assert_eq!(CpuState::Running, cpu.state);
+        assert!(cpu.halt_bug);
+
+        cpu.step().unwrap();
+
+        // INC A is read once without incrementing pc, so it runs twice
+        assert_eq!(0x1, cpu.registers.a);
+        assert_eq!(PROGRAM_ADDRESS + 1, cpu.program_counter);
+
+        cpu.step().unwrap();
+
+        assert_eq!(0x2, cpu.registers.a);
+        assert_eq!(PROGRAM_ADDRESS + 2, cpu.program_counter);
+    }
+
+    #[test]
+    fn test_halt_bug_after_ei(){
+        // EI; HALT; NOP
+        let mut cpu = cpu_with_program(&[0xFB, 0x76, 0x00]);
+        cpu.bus.write_byte(0xFFFF, 0x04);
+        cpu.bus.request_interrupt(Interrupt::Timer);
+
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+
+        // the interrupt returns to HALT itself
+        assert_eq!(0x50, cpu.program_counter);
+        assert_eq!(PROGRAM_ADDRESS + 1, cpu.bus.read_word(cpu.stack_pointer));
+        assert!(!cpu.halt_bug);
+    }
+
+    #[test]
+    fn test_stop_waits_for_joypad(){
+        // STOP; padding; INC A
+        let mut cpu = cpu_with_program(&[0x10, 0x00, 0x3C]);
+        cpu.bus.write_byte(0xFF00, 0x00);
+
+        cpu.step().unwrap();
+
+        assert_eq!(CpuState::Stopped, cpu.state);
+        assert_eq!(PROGRAM_ADDRESS + 2, cpu.program_counter);
+
+        cpu.bus.request_interrupt(Interrupt::Timer);
+        cpu.bus.write_byte(0xFFFF, 0xFF);
+        cpu.step().unwrap();
+
+        assert_eq!(CpuState::Stopped, cpu.state);
+
+        cpu.bus.press_button(Button::A);
+        cpu.step().unwrap();
+
+        assert_eq!(CpuState::Running, cpu.state);
+        assert_eq!(0x1, cpu.registers.a);
+    }
+
+    #[test]
+    fn test_stop_resets_div(){
+        let mut cpu = cpu_with_program(&[0x10, 0x00]);
+        cpu.bus.tick(0xFF);
+        cpu.bus.tick(0xFF);
+
+        assert_eq!(0x07, cpu.bus.read_byte(0xFF04));
+
+        cpu.step().unwrap();
+
+        assert_eq!(0x00, cpu.bus.read_byte(0xFF04));
+    }
+
+    #[test]
+    fn test_stop_ignores_unselected_buttons(){
+        let mut cpu = cpu_with_program(&[0x10, 0x00]);
+        cpu.bus.write_byte(0xFF00, 0x20);
+
+        cpu.step().unwrap();
+        cpu.bus.press_button(Button::A);
+        cpu.step().unwrap();
+
+        assert_eq!(CpuState::Stopped, cpu.state);
+
+        cpu.bus.press_button(Button::Up);
+        cpu.step().unwrap();
+
+        assert_eq!(CpuState::Running, cpu.state);
+    }
+
+    #[test]
+    fn test_stop_without_armed_switch_on_cgb(){
+        let mut cpu = cpu_with_program(&[0x10, 0x00]);
+        cpu.bus.cgb_mode = true;
+
+        cpu.step().unwrap();
+
+        assert_eq!(CpuState::Stopped, cpu.state);
+        assert!(!cpu.bus.double_speed);
+    }
+
+    #[test]
+    fn test_stop_switches_speed(){
+        // STOP; padding; INC A
+        let mut cpu = cpu_with_program(&[0x10, 0x00, 0x3C]);
+        cpu.bus.cgb_mode = true;
+        cpu.bus.write_byte(0xFF4D, 0x01);
+
+        cpu.step().unwrap();
+
+        assert!(cpu.bus.double_speed);
+        assert_eq!(0xFE, cpu.bus.read_byte(0xFF4D));
+        assert_eq!(CpuState::SwitchingSpeed { remaining_cycles: SPEED_SWITCH_CYCLES }, cpu.state);
+
+        for _ in 0..SPEED_SWITCH_CYCLES {
+            assert_eq!(1, cpu.step().unwrap());
+        }
+
+        assert_eq!(CpuState::Running, cpu.state);
+        assert_eq!(0x0, cpu.registers.a);
+
+        cpu.step().unwrap();
+
+        assert_eq!(0x1, cpu.registers.a);
+    }
+}
@@ -3,4 +3,8 @@ mod utils;
 mod arithmetic_8;
 mod load_8;
 mod jump;
-mod load_16;
\ No newline at end of file
+mod load_16;
+mod arithmetic_16;
+mod bit_operations;
+mod interrupts;
+mod low_power;
\ No newline at end of file
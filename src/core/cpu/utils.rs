@@ -1,12 +1,49 @@
-use crate::core::cpu::base::CPU;
+use crate::core::cpu::base::{CPU, TimingMode};
+#[cfg(test)]
+use crate::core::cpu::base::BusAccess;
 use crate::core::instructions::definitions::{PushPopTarget, RegisterTarget, RegisterTarget16};
-use crate::util::join_u8;
+use crate::util::{join_u8, split_u16};
 
 impl CPU{
+    // Every bus access made by an instruction takes one M-cycle
+    pub (super) fn read_byte(&mut self, address: u16) -> u8 {
+        self.tick();
+        #[cfg(test)]
+        self.bus_accesses.push(BusAccess::Read { cycle: self.bus.cycles, address });
+        self.bus.read_byte(address)
+    }
+
+    pub (super) fn write_byte(&mut self, address: u16, value: u8) {
+        self.tick();
+        #[cfg(test)]
+        self.bus_accesses.push(BusAccess::Write { cycle: self.bus.cycles, address, value });
+        self.bus.write_byte(address, value);
+    }
+
+    pub (super) fn read_word(&mut self, lsb_address: u16) -> u16 {
+        let lsb_value = self.read_byte(lsb_address);
+        let msb_value = self.read_byte(lsb_address.wrapping_add(1));
+        join_u8(msb_value, lsb_value)
+    }
+
+    pub (super) fn write_word(&mut self, lsb_address: u16, word: u16) {
+        let (msb_word, lsb_word) = split_u16(word);
+        self.write_byte(lsb_address, lsb_word);
+        self.write_byte(lsb_address.wrapping_add(1), msb_word);
+    }
+
+    // Advances by one M-cycle, either for a bus access or for internal work that doesn't touch the bus
+    pub (super) fn tick(&mut self) {
+        self.cycles += 1;
+        if self.timing_mode == TimingMode::MCycle {
+            self.bus.tick(1);
+        }
+    }
+
     pub (super) fn read_byte_and_increment_pc(&mut self) -> u8 {
         let address = self.program_counter;
         self.program_counter = address.wrapping_add(1);
-        self.bus.read_byte(address)
+        self.read_byte(address)
     }
 
     pub (super) fn read_word_and_increment_pc(&mut self) -> u16 {
@@ -15,6 +52,11 @@ impl CPU{
         join_u8(msb_address, lsb_address)
     }
 
+    pub (super) fn read_indirect_hl(&mut self) -> u8 {
+        let address = self.registers.get_hl();
+        self.read_byte(address)
+    }
+
     pub (super) fn get_register_value(&mut self, target: RegisterTarget) -> u8 {
         match target {
             RegisterTarget::A => self.registers.a,
@@ -84,14 +126,16 @@ impl CPU{
 #[cfg(test)]
 mod test{
     use strum::IntoEnumIterator;
+    use crate::core::cartridge::base::Cartridge;
+    use crate::core::cartridge::header::{fix_checksums, test_rom};
     use crate::core::cpu::base::CPU;
     use crate::core::instructions::definitions::{RegisterTarget, RegisterTarget16};
-    use crate::core::registers::AF_BIT_MASK;
+    use crate::core::model::Model;
     use crate::util::{join_u8, Randomizable};
 
     #[test]
     fn test_read_and_increment_pc(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         let address = 0xFFFF;
         let value = 0x12;
         cpu.program_counter = address;
@@ -104,16 +148,19 @@ mod test{
     }
     #[test]
     fn test_read_address_and_increment_pc(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         let lsb_stored_address = 0xFFFF;
-        let msb_stored_address = 0x0;
+        let msb_stored_address: u16 = 0x0;
         let lsb_target_address = 0x12;
         let msb_target_address = 0x20;
 
         cpu.program_counter = lsb_stored_address;
 
         cpu.bus.write_byte(lsb_stored_address, lsb_target_address);
-        cpu.bus.write_byte(msb_stored_address, msb_target_address);
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        rom[msb_stored_address as usize] = msb_target_address;
+        fix_checksums(&mut rom);
+        cpu.bus.insert_cartridge(Cartridge::new(rom).unwrap());
 
         let result = cpu.read_word_and_increment_pc();
 
@@ -125,7 +172,7 @@ mod test{
 
     #[test]
     fn test_set_register_value(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         for target in RegisterTarget::iter(){
             let val = u8::random();
             cpu.set_register_value(target, val);
@@ -142,7 +189,7 @@ mod test{
 
     #[test]
     fn test_get_register_value_and_pointer(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         for target in RegisterTarget::iter(){
             assert_eq!(0x0, *cpu.get_register_pointer(target));
         }
@@ -156,16 +203,35 @@ mod test{
 
     #[test]
     fn test_get_set_register_value_16(){
-        let mut cpu = CPU::new();
+        let mut cpu = CPU::new(Model::Dmg);
         for target in RegisterTarget16::iter(){
             assert_eq!(0x0, cpu.get_register_value_16(target));
         }
         for target in RegisterTarget16::iter(){
-            let mut val = u16::random();
+            let val = u16::random();
             cpu.set_register_value_16(target, val);
 
             assert_eq!(val, cpu.get_register_value_16(target));
         }
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_bus_access_ticks(){
+        let mut cpu = CPU::new(Model::Dmg);
+
+        cpu.write_byte(0xC000, 0x12);
+        assert_eq!(1, cpu.cycles);
+
+        assert_eq!(0x12, cpu.read_byte(0xC000));
+        assert_eq!(2, cpu.cycles);
+
+        cpu.write_word(0xC000, 0x3456);
+        assert_eq!(4, cpu.cycles);
+
+        assert_eq!(0x3456, cpu.read_word(0xC000));
+        assert_eq!(6, cpu.cycles);
+
+        cpu.tick();
+        assert_eq!(7, cpu.cycles);
+    }
+}
@@ -2,6 +2,16 @@ use strum::EnumIter;
 
 #[derive(Debug, Clone, Copy, PartialEq, Hash)]
 pub(crate) enum Instruction {
+    // Does nothing for one cycle
+    NoOperation,
+    // Resets IME, disabling the interrupts
+    DisableInterrupts,
+    // Sets IME after the next instruction, enabling the interrupts
+    EnableInterrupts,
+    // Suspends execution until an interrupt is pending
+    Halt,
+    // Enters the low power mode until a button is pressed, or switches speed on CGB
+    Stop,
     // Adds to a, value from register R. Sets flags.
     AddRegister(RegisterTarget),
     // Adds to a, value from address specified by Hl. Sets flags.
@@ -14,7 +24,76 @@ pub(crate) enum Instruction {
     AdcIndirectHl,
     // Adds to a, the value N (from pc) and carry. Sets flags.
     AddCarryN,
-    // SUB(ArithmeticTarget)
+    // Subtracts from a, value from register R. Sets flags.
+    SubRegister(RegisterTarget),
+    // Subtracts from a, value from address specified by Hl. Sets flags.
+    SubIndirectHl,
+    // Subtracts from a, the value N (from pc). Sets flags.
+    SubN,
+    // Subtracts from a, value from register R and carry. Sets flags.
+    SubCarryRegister(RegisterTarget),
+    // Subtracts from a, value from address specified by Hl and carry. Sets flags.
+    SbcIndirectHl,
+    // Subtracts from a, the value N (from pc) and carry. Sets flags.
+    SubCarryN,
+    // Bitwise AND between a and value from register R. Sets flags.
+    AndRegister(RegisterTarget),
+    // Bitwise AND between a and value from address specified by Hl. Sets flags.
+    AndIndirectHl,
+    // Bitwise AND between a and the value N (from pc). Sets flags.
+    AndN,
+    // Bitwise OR between a and value from register R. Sets flags.
+    OrRegister(RegisterTarget),
+    // Bitwise OR between a and value from address specified by Hl. Sets flags.
+    OrIndirectHl,
+    // Bitwise OR between a and the value N (from pc). Sets flags.
+    OrN,
+    // Bitwise XOR between a and value from register R. Sets flags.
+    XorRegister(RegisterTarget),
+    // Bitwise XOR between a and value from address specified by Hl. Sets flags.
+    XorIndirectHl,
+    // Bitwise XOR between a and the value N (from pc). Sets flags.
+    XorN,
+    // Subtracts from a, value from register R, without storing the result. Sets flags.
+    CompareRegister(RegisterTarget),
+    // Subtracts from a, value from address specified by Hl, without storing the result. Sets flags.
+    CompareIndirectHl,
+    // Subtracts from a, the value N (from pc), without storing the result. Sets flags.
+    CompareN,
+    // Increments register R by 1. Sets flags, except carry.
+    IncrementRegister(RegisterTarget),
+    // Increments the value at the address specified by Hl by 1. Sets flags, except carry.
+    IncrementIndirectHl,
+    // Decrements register R by 1. Sets flags, except carry.
+    DecrementRegister(RegisterTarget),
+    // Decrements the value at the address specified by Hl by 1. Sets flags, except carry.
+    DecrementIndirectHl,
+    // Adds to Hl, value from the 16 bit register RR. Sets flags, except zero.
+    AddHlRegister16(RegisterTarget16),
+    // Increments the 16 bit register RR by 1. Doesn't set flags.
+    IncrementRegister16(RegisterTarget16),
+    // Decrements the 16 bit register RR by 1. Doesn't set flags.
+    DecrementRegister16(RegisterTarget16),
+    // Adds to SP, the signed value E (from pc). Sets flags.
+    AddStackPointerE,
+    // Loads to Hl, the sum of SP and the signed value E (from pc). Sets flags.
+    LoadHlStackPointerE,
+    // Adjusts a to a valid BCD number, after an addition or a subtraction. Sets flags, except subtract.
+    DecimalAdjustA,
+    // Flips all the bits of a. Sets flags.
+    ComplementA,
+    // Sets the carry flag. Resets subtract and half carry.
+    SetCarryFlag,
+    // Flips the carry flag. Resets subtract and half carry.
+    ComplementCarryFlag,
+    // Rotates a left, bit 7 goes to carry and bit 0. Resets zero.
+    RotateLeftCircularA,
+    // Rotates a right, bit 0 goes to carry and bit 7. Resets zero.
+    RotateRightCircularA,
+    // Rotates a left through carry. Resets zero.
+    RotateLeftA,
+    // Rotates a right through carry. Resets zero.
+    RotateRightA,
     // Loads data from the second register to the first
     LoadRegisterRegister(RegisterTarget, RegisterTarget),
     // Loads value N (from pc) into register R
@@ -66,7 +145,69 @@ pub(crate) enum Instruction {
     // Unconditional jump to the nn address (indirect pc)
     JumpToNn,
     // Jumps to the nn address (indirect pc) if the JumpCondition is satisfied
-    JumpConditionalToNn(JumpCondition)
+    JumpConditionalToNn(JumpCondition),
+    // Unconditional jump to the address specified by Hl
+    JumpToHl,
+    // Unconditional jump relative to pc, by the signed value E (from pc)
+    JumpRelative,
+    // Jumps relative to pc, by the signed value E (from pc), if the JumpCondition is satisfied
+    JumpConditionalRelative(JumpCondition),
+    // Pushes pc to the stack, then jumps to the nn address (indirect pc)
+    CallNn,
+    // Pushes pc to the stack, then jumps to the nn address (indirect pc), if the JumpCondition is satisfied
+    CallConditionalNn(JumpCondition),
+    // Pops pc from the stack
+    Return,
+    // Pops pc from the stack if the JumpCondition is satisfied
+    ReturnConditional(JumpCondition),
+    // Pops pc from the stack and enables interrupts
+    ReturnInterrupt,
+    // Pushes pc to the stack, then jumps to the fixed address 0x00XX
+    Restart(u8),
+    // Rotates register R left, bit 7 goes to carry and bit 0. Sets flags.
+    RotateLeftCircularRegister(RegisterTarget),
+    // Rotates the value at the address specified by Hl left, bit 7 goes to carry and bit 0. Sets flags.
+    RotateLeftCircularIndirectHl,
+    // Rotates register R right, bit 0 goes to carry and bit 7. Sets flags.
+    RotateRightCircularRegister(RegisterTarget),
+    // Rotates the value at the address specified by Hl right, bit 0 goes to carry and bit 7. Sets flags.
+    RotateRightCircularIndirectHl,
+    // Rotates register R left through carry. Sets flags.
+    RotateLeftRegister(RegisterTarget),
+    // Rotates the value at the address specified by Hl left through carry. Sets flags.
+    RotateLeftIndirectHl,
+    // Rotates register R right through carry. Sets flags.
+    RotateRightRegister(RegisterTarget),
+    // Rotates the value at the address specified by Hl right through carry. Sets flags.
+    RotateRightIndirectHl,
+    // Shifts register R left, bit 7 goes to carry and bit 0 is reset. Sets flags.
+    ShiftLeftArithmeticRegister(RegisterTarget),
+    // Shifts the value at the address specified by Hl left, bit 7 goes to carry and bit 0 is reset. Sets flags.
+    ShiftLeftArithmeticIndirectHl,
+    // Shifts register R right, bit 0 goes to carry and bit 7 is kept. Sets flags.
+    ShiftRightArithmeticRegister(RegisterTarget),
+    // Shifts the value at the address specified by Hl right, bit 0 goes to carry and bit 7 is kept. Sets flags.
+    ShiftRightArithmeticIndirectHl,
+    // Swaps the upper and lower nibbles of register R. Sets flags.
+    SwapRegister(RegisterTarget),
+    // Swaps the upper and lower nibbles of the value at the address specified by Hl. Sets flags.
+    SwapIndirectHl,
+    // Shifts register R right, bit 0 goes to carry and bit 7 is reset. Sets flags.
+    ShiftRightLogicalRegister(RegisterTarget),
+    // Shifts the value at the address specified by Hl right, bit 0 goes to carry and bit 7 is reset. Sets flags.
+    ShiftRightLogicalIndirectHl,
+    // Tests bit B of register R. Sets flags, except carry.
+    BitRegister(u8, RegisterTarget),
+    // Tests bit B of the value at the address specified by Hl. Sets flags, except carry.
+    BitIndirectHl(u8),
+    // Resets bit B of register R
+    ResetRegister(u8, RegisterTarget),
+    // Resets bit B of the value at the address specified by Hl
+    ResetIndirectHl(u8),
+    // Sets bit B of register R
+    SetRegister(u8, RegisterTarget),
+    // Sets bit B of the value at the address specified by Hl
+    SetIndirectHl(u8)
 }
 
 #[derive(Debug, Clone, Copy, EnumIter, PartialEq, Hash)]
@@ -1,4 +1,4 @@
-use crate::core::instructions::definitions::{Instruction, PushPopTarget};
+use crate::core::instructions::definitions::{Instruction, PushPopTarget, RegisterTarget};
 use crate::core::instructions::definitions::Instruction::*;
 use crate::core::instructions::definitions::JumpCondition::*;
 use crate::core::instructions::definitions::RegisterTarget::*;
@@ -12,57 +12,91 @@ impl Instruction {
 
     pub(crate) fn from_byte(byte: u8, is_prefixed: bool) -> Option<Instruction> {
         if is_prefixed {
-            PREFIX_INSTRUCTION_ARR[byte as usize]
+            Instruction::from_byte_prefixed(byte)
         } else {
             INSTRUCTION_ARR[byte as usize]
         }
     }
 
     fn from_byte_prefixed(byte: u8) -> Option<Instruction>{
-        None
+        PREFIX_INSTRUCTION_ARR[byte as usize]
     }
 }
 
 const fn init_instruction_array() -> [Option<Instruction>; 256] {
     let mut a = [None; 256];
 
+    a[0x00] = Some(NoOperation);
     a[0x01] = Some(LoadRegister16Nn(BC));
     a[0x02] = Some(LoadIndirectBcA);
-    
+    a[0x03] = Some(IncrementRegister16(BC));
+    a[0x04] = Some(IncrementRegister(B));
+    a[0x05] = Some(DecrementRegister(B));
     a[0x06] = Some(LoadRegisterN(B));
+    a[0x07] = Some(RotateLeftCircularA);
 
     a[0x08] = Some(LoadNnFromStackPointer);
-
+    a[0x09] = Some(AddHlRegister16(BC));
     a[0x0A] = Some(LoadAIndirectBc);
-    
+    a[0x0B] = Some(DecrementRegister16(BC));
+    a[0x0C] = Some(IncrementRegister(C));
+    a[0x0D] = Some(DecrementRegister(C));
     a[0x0E] = Some(LoadRegisterN(C));
+    a[0x0F] = Some(RotateRightCircularA);
 
+    a[0x10] = Some(Stop);
     a[0x11] = Some(LoadRegister16Nn(DE));
     a[0x12] = Some(LoadIndirectDeA);
-    
+    a[0x13] = Some(IncrementRegister16(DE));
+    a[0x14] = Some(IncrementRegister(D));
+    a[0x15] = Some(DecrementRegister(D));
     a[0x16] = Some(LoadRegisterN(D));
-    
+    a[0x17] = Some(RotateLeftA);
+
+    a[0x18] = Some(JumpRelative);
+    a[0x19] = Some(AddHlRegister16(DE));
     a[0x1A] = Some(LoadAIndirectDe);
-    
+    a[0x1B] = Some(DecrementRegister16(DE));
+    a[0x1C] = Some(IncrementRegister(E));
+    a[0x1D] = Some(DecrementRegister(E));
     a[0x1E] = Some(LoadRegisterN(E));
+    a[0x1F] = Some(RotateRightA);
 
+    a[0x20] = Some(JumpConditionalRelative(NotZero));
     a[0x21] = Some(LoadRegister16Nn(HL));
     a[0x22] = Some(LoadIndirectHlIncrementA);
-    
+    a[0x23] = Some(IncrementRegister16(HL));
+    a[0x24] = Some(IncrementRegister(H));
+    a[0x25] = Some(DecrementRegister(H));
     a[0x26] = Some(LoadRegisterN(H));
-    
+    a[0x27] = Some(DecimalAdjustA);
+
+    a[0x28] = Some(JumpConditionalRelative(Zero));
+    a[0x29] = Some(AddHlRegister16(HL));
     a[0x2A] = Some(LoadAIndirectHlIncrement);
-    
+    a[0x2B] = Some(DecrementRegister16(HL));
+    a[0x2C] = Some(IncrementRegister(L));
+    a[0x2D] = Some(DecrementRegister(L));
     a[0x2E] = Some(LoadRegisterN(L));
+    a[0x2F] = Some(ComplementA);
 
+    a[0x30] = Some(JumpConditionalRelative(NotCarry));
     a[0x31] = Some(LoadRegister16Nn(SP));
     a[0x32] = Some(LoadIndirectHlDecrementA);
-    
+    a[0x33] = Some(IncrementRegister16(SP));
+    a[0x34] = Some(IncrementIndirectHl);
+    a[0x35] = Some(DecrementIndirectHl);
     a[0x36] = Some(LoadIndirectHlN);
-    
+    a[0x37] = Some(SetCarryFlag);
+
+    a[0x38] = Some(JumpConditionalRelative(Carry));
+    a[0x39] = Some(AddHlRegister16(SP));
     a[0x3A] = Some(LoadAIndirectHlDecrement);
-    
+    a[0x3B] = Some(DecrementRegister16(SP));
+    a[0x3C] = Some(IncrementRegister(A));
+    a[0x3D] = Some(DecrementRegister(A));
     a[0x3E] = Some(LoadRegisterN(A));
+    a[0x3F] = Some(ComplementCarryFlag);
     
     a[0x40] = Some(LoadRegisterRegister(B, B));
     a[0x41] = Some(LoadRegisterRegister(B, C));
@@ -118,7 +152,7 @@ const fn init_instruction_array() -> [Option<Instruction>; 256] {
     a[0x73] = Some(LoadIndirectHlRegister(E));
     a[0x74] = Some(LoadIndirectHlRegister(H));
     a[0x75] = Some(LoadIndirectHlRegister(L));
-    
+    a[0x76] = Some(Halt);
     a[0x77] = Some(LoadIndirectHlRegister(A));
     a[0x78] = Some(LoadRegisterRegister(A, B));
     a[0x79] = Some(LoadRegisterRegister(A, C));
@@ -144,24 +178,88 @@ const fn init_instruction_array() -> [Option<Instruction>; 256] {
     a[0x8D] = Some(AddCarryRegister(L));
     a[0x8E] = Some(AdcIndirectHl);
     a[0x8F] = Some(AddCarryRegister(A));
-
+    a[0x90] = Some(SubRegister(B));
+    a[0x91] = Some(SubRegister(C));
+    a[0x92] = Some(SubRegister(D));
+    a[0x93] = Some(SubRegister(E));
+    a[0x94] = Some(SubRegister(H));
+    a[0x95] = Some(SubRegister(L));
+    a[0x96] = Some(SubIndirectHl);
+    a[0x97] = Some(SubRegister(A));
+    a[0x98] = Some(SubCarryRegister(B));
+    a[0x99] = Some(SubCarryRegister(C));
+    a[0x9A] = Some(SubCarryRegister(D));
+    a[0x9B] = Some(SubCarryRegister(E));
+    a[0x9C] = Some(SubCarryRegister(H));
+    a[0x9D] = Some(SubCarryRegister(L));
+    a[0x9E] = Some(SbcIndirectHl);
+    a[0x9F] = Some(SubCarryRegister(A));
+    a[0xA0] = Some(AndRegister(B));
+    a[0xA1] = Some(AndRegister(C));
+    a[0xA2] = Some(AndRegister(D));
+    a[0xA3] = Some(AndRegister(E));
+    a[0xA4] = Some(AndRegister(H));
+    a[0xA5] = Some(AndRegister(L));
+    a[0xA6] = Some(AndIndirectHl);
+    a[0xA7] = Some(AndRegister(A));
+    a[0xA8] = Some(XorRegister(B));
+    a[0xA9] = Some(XorRegister(C));
+    a[0xAA] = Some(XorRegister(D));
+    a[0xAB] = Some(XorRegister(E));
+    a[0xAC] = Some(XorRegister(H));
+    a[0xAD] = Some(XorRegister(L));
+    a[0xAE] = Some(XorIndirectHl);
+    a[0xAF] = Some(XorRegister(A));
+    a[0xB0] = Some(OrRegister(B));
+    a[0xB1] = Some(OrRegister(C));
+    a[0xB2] = Some(OrRegister(D));
+    a[0xB3] = Some(OrRegister(E));
+    a[0xB4] = Some(OrRegister(H));
+    a[0xB5] = Some(OrRegister(L));
+    a[0xB6] = Some(OrIndirectHl);
+    a[0xB7] = Some(OrRegister(A));
+    a[0xB8] = Some(CompareRegister(B));
+    a[0xB9] = Some(CompareRegister(C));
+    a[0xBA] = Some(CompareRegister(D));
+    a[0xBB] = Some(CompareRegister(E));
+    a[0xBC] = Some(CompareRegister(H));
+    a[0xBD] = Some(CompareRegister(L));
+    a[0xBE] = Some(CompareIndirectHl);
+    a[0xBF] = Some(CompareRegister(A));
+
+    a[0xC0] = Some(ReturnConditional(NotZero));
     a[0xC1] = Some(PopIntoRegister(PushPopTarget::BC));
     a[0xC2] = Some(JumpConditionalToNn(NotZero));
     a[0xC3] = Some(JumpToNn);
-
+    a[0xC4] = Some(CallConditionalNn(NotZero));
     a[0xC5] = Some(PushFromRegister(PushPopTarget::BC));
     a[0xC6] = Some(AddN);
-
+    a[0xC7] = Some(Restart(0x00));
+    a[0xC8] = Some(ReturnConditional(Zero));
+    a[0xC9] = Some(Return);
     a[0xCA] = Some(JumpConditionalToNn(Zero));
 
+    a[0xCC] = Some(CallConditionalNn(Zero));
+    a[0xCD] = Some(CallNn);
     a[0xCE] = Some(AddCarryN);
+    a[0xCF] = Some(Restart(0x08));
 
+    a[0xD0] = Some(ReturnConditional(NotCarry));
     a[0xD1] = Some(PopIntoRegister(PushPopTarget::DE));
     a[0xD2] = Some(JumpConditionalToNn(NotCarry));
-
-    a[0xDA] = Some(JumpConditionalToNn(Carry));
-
+    // None
+    a[0xD4] = Some(CallConditionalNn(NotCarry));
     a[0xD5] = Some(PushFromRegister(PushPopTarget::DE));
+    a[0xD6] = Some(SubN);
+    a[0xD7] = Some(Restart(0x10));
+    a[0xD8] = Some(ReturnConditional(Carry));
+    a[0xD9] = Some(ReturnInterrupt);
+    a[0xDA] = Some(JumpConditionalToNn(Carry));
+    // None
+    a[0xDC] = Some(CallConditionalNn(Carry));
+    // None
+    a[0xDE] = Some(SubCarryN);
+    a[0xDF] = Some(Restart(0x18));
 
     a[0xE0] = Some(LoadHalfNA);
 
@@ -171,31 +269,104 @@ const fn init_instruction_array() -> [Option<Instruction>; 256] {
     // None
 
     a[0xE5] = Some(PushFromRegister(PushPopTarget::HL));
+    a[0xE6] = Some(AndN);
+    a[0xE7] = Some(Restart(0x20));
 
+    a[0xE8] = Some(AddStackPointerE);
+    a[0xE9] = Some(JumpToHl);
     a[0xEA] = Some(LoadNnA);
 
+    a[0xEE] = Some(XorN);
+    a[0xEF] = Some(Restart(0x28));
+
     a[0xF0] = Some(LoadHalfAN);
 
     a[0xF1] = Some(PopIntoRegister(PushPopTarget::AF));
     a[0xF2] = Some(LoadHalfAC);
+    a[0xF3] = Some(DisableInterrupts);
 
     a[0xFA] = Some(LoadANn);
+    a[0xFB] = Some(EnableInterrupts);
 
     a[0xF5] = Some(PushFromRegister(PushPopTarget::AF));
+    a[0xF6] = Some(OrN);
+    a[0xF7] = Some(Restart(0x30));
 
+    a[0xF8] = Some(LoadHlStackPointerE);
     a[0xF9] = Some(LoadStackPointerFromHl);
 
+    a[0xFE] = Some(CompareN);
+    a[0xFF] = Some(Restart(0x38));
+
     a
 }
 
+// Every prefixed opcode is laid out as 0bOOOOOTTT or 0bOOBBBTTT, where O selects the operation,
+// B the bit (for BIT, RES and SET) and T the target, in the order B, C, D, E, H, L, (HL), A
 const fn init_prefix_instruction_array() -> [Option<Instruction>; 256] {
-    let mut array = [None; 256];
-    array
+    let mut a = [None; 256];
+
+    let mut opcode = 0;
+    while opcode < 256 {
+        let bit = ((opcode >> 3) & 0b111) as u8;
+        let instruction = match prefix_register_target(opcode) {
+            Some(target) => match opcode >> 3 {
+                0x0 => RotateLeftCircularRegister(target),
+                0x1 => RotateRightCircularRegister(target),
+                0x2 => RotateLeftRegister(target),
+                0x3 => RotateRightRegister(target),
+                0x4 => ShiftLeftArithmeticRegister(target),
+                0x5 => ShiftRightArithmeticRegister(target),
+                0x6 => SwapRegister(target),
+                0x7 => ShiftRightLogicalRegister(target),
+                _ => match opcode >> 6 {
+                    0x1 => BitRegister(bit, target),
+                    0x2 => ResetRegister(bit, target),
+                    _ => SetRegister(bit, target)
+                }
+            },
+            None => match opcode >> 3 {
+                0x0 => RotateLeftCircularIndirectHl,
+                0x1 => RotateRightCircularIndirectHl,
+                0x2 => RotateLeftIndirectHl,
+                0x3 => RotateRightIndirectHl,
+                0x4 => ShiftLeftArithmeticIndirectHl,
+                0x5 => ShiftRightArithmeticIndirectHl,
+                0x6 => SwapIndirectHl,
+                0x7 => ShiftRightLogicalIndirectHl,
+                _ => match opcode >> 6 {
+                    0x1 => BitIndirectHl(bit),
+                    0x2 => ResetIndirectHl(bit),
+                    _ => SetIndirectHl(bit)
+                }
+            }
+        };
+        a[opcode] = Some(instruction);
+        opcode += 1;
+    }
+
+    a
+}
+
+// None stands for (HL)
+const fn prefix_register_target(opcode: usize) -> Option<RegisterTarget> {
+    match opcode & 0b111 {
+        0x0 => Some(RegisterTarget::B),
+        0x1 => Some(RegisterTarget::C),
+        0x2 => Some(RegisterTarget::D),
+        0x3 => Some(RegisterTarget::E),
+        0x4 => Some(RegisterTarget::H),
+        0x5 => Some(RegisterTarget::L),
+        0x6 => None,
+        _ => Some(RegisterTarget::A)
+    }
 }
 
 #[cfg(test)]
 mod test{
     use crate::core::instructions::definitions::Instruction;
+    use crate::core::instructions::definitions::Instruction::*;
+    use crate::core::instructions::definitions::RegisterTarget::*;
     use crate::core::instructions::mapping::{init_instruction_array, init_prefix_instruction_array};
 
     #[test]
@@ -208,13 +379,50 @@ mod test{
         test_no_duplicates(init_prefix_instruction_array());
     }
 
+    #[test]
+    fn test_every_prefixed_byte_decodes(){
+        for byte in 0..=u8::MAX {
+            assert!(Instruction::from_byte(byte, true).is_some(), "0xcb{:02x} does not decode", byte);
+        }
+    }
+
+    #[test]
+    fn test_prefixed_decoding(){
+        let expected = [
+            (0x00, RotateLeftCircularRegister(B)),
+            (0x06, RotateLeftCircularIndirectHl),
+            (0x0F, RotateRightCircularRegister(A)),
+            (0x11, RotateLeftRegister(C)),
+            (0x1E, RotateRightIndirectHl),
+            (0x22, ShiftLeftArithmeticRegister(D)),
+            (0x2B, ShiftRightArithmeticRegister(E)),
+            (0x34, SwapRegister(H)),
+            (0x36, SwapIndirectHl),
+            (0x3D, ShiftRightLogicalRegister(L)),
+            (0x3E, ShiftRightLogicalIndirectHl),
+            (0x40, BitRegister(0, B)),
+            (0x46, BitIndirectHl(0)),
+            (0x7C, BitRegister(7, H)),
+            (0x7E, BitIndirectHl(7)),
+            (0x87, ResetRegister(0, A)),
+            (0x9E, ResetIndirectHl(3)),
+            (0xBF, ResetRegister(7, A)),
+            (0xC0, SetRegister(0, B)),
+            (0xE6, SetIndirectHl(4)),
+            (0xFF, SetRegister(7, A)),
+        ];
+        for (byte, instruction) in expected {
+            assert_eq!(Some(instruction), Instruction::from_byte(byte, true), "0xcb{:02x}", byte);
+        }
+    }
+
     fn test_no_duplicates(array: [Option<Instruction>; 256]){
         for (i, el1) in array.iter().enumerate() {
-            if *el1 == None {
+            if el1.is_none() {
                 continue;
             }
             for el2 in &array[(i + 1)..] {
-                if *el2 == None {
+                if el2.is_none() {
                     continue;
                 }
                 assert_ne!((*el1).unwrap(), (*el2).unwrap());
@@ -0,0 +1,174 @@
+use strum::EnumIter;
+
+pub(super) const INTERRUPT_FLAG_ADDRESS: u16 = 0xFF0F;
+pub(super) const INTERRUPT_ENABLE_ADDRESS: u16 = 0xFFFF;
+
+// Only the lower 5 bits of IF are wired, the others always read as 1
+const INTERRUPT_FLAG_UNUSED_BITS: u8 = 0xE0;
+
+// Ordered by priority, VBlank being the highest
+#[derive(Debug, Clone, Copy, EnumIter, PartialEq)]
+pub(crate) enum Interrupt {
+    VBlank,
+    LcdStat,
+    Timer,
+    Serial,
+    Joypad
+}
+
+impl Interrupt {
+    fn bit(self) -> u8 {
+        match self {
+            Interrupt::VBlank => 0b00001,
+            Interrupt::LcdStat => 0b00010,
+            Interrupt::Timer => 0b00100,
+            Interrupt::Serial => 0b01000,
+            Interrupt::Joypad => 0b10000
+        }
+    }
+
+    pub(super) fn vector(self) -> u16 {
+        match self {
+            Interrupt::VBlank => 0x40,
+            Interrupt::LcdStat => 0x48,
+            Interrupt::Timer => 0x50,
+            Interrupt::Serial => 0x58,
+            Interrupt::Joypad => 0x60
+        }
+    }
+
+    fn from_bit_index(index: u32) -> Interrupt {
+        match index {
+            0 => Interrupt::VBlank,
+            1 => Interrupt::LcdStat,
+            2 => Interrupt::Timer,
+            3 => Interrupt::Serial,
+            _ => Interrupt::Joypad
+        }
+    }
+}
+
+// Holds the IE (0xFFFF) and IF (0xFF0F) registers
+#[derive(Debug)]
+pub(super) struct InterruptController {
+    enable: u8,
+    flag: u8
+}
+
+impl InterruptController {
+    pub(super) fn new() -> Self {
+        InterruptController {
+            enable: 0,
+            flag: 0
+        }
+    }
+
+    pub(super) fn request(&mut self, interrupt: Interrupt) {
+        self.flag |= interrupt.bit();
+    }
+
+    pub(super) fn acknowledge(&mut self, interrupt: Interrupt) {
+        self.flag &= !interrupt.bit();
+    }
+
+    // The highest priority interrupt that is both requested and enabled, regardless of IME
+    pub(super) fn pending(&self) -> Option<Interrupt> {
+        let pending = self.enable & self.flag & !INTERRUPT_FLAG_UNUSED_BITS;
+        if pending == 0 {
+            None
+        } else {
+            Some(Interrupt::from_bit_index(pending.trailing_zeros()))
+        }
+    }
+
+    pub(super) fn read_enable(&self) -> u8 {
+        self.enable
+    }
+
+    pub(super) fn write_enable(&mut self, value: u8) {
+        self.enable = value;
+    }
+
+    pub(super) fn read_flag(&self) -> u8 {
+        self.flag | INTERRUPT_FLAG_UNUSED_BITS
+    }
+
+    pub(super) fn write_flag(&mut self, value: u8) {
+        self.flag = value & !INTERRUPT_FLAG_UNUSED_BITS;
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use strum::IntoEnumIterator;
+    use crate::core::interrupts::{Interrupt, InterruptController};
+
+    #[test]
+    fn test_request_and_acknowledge(){
+        let mut controller = InterruptController::new();
+        controller.write_enable(0x1F);
+
+        for interrupt in Interrupt::iter() {
+            controller.request(interrupt);
+
+            assert_eq!(Some(interrupt), controller.pending());
+
+            controller.acknowledge(interrupt);
+
+            assert_eq!(None, controller.pending());
+        }
+    }
+
+    #[test]
+    fn test_pending_needs_enable(){
+        let mut controller = InterruptController::new();
+        controller.request(Interrupt::Timer);
+
+        assert_eq!(None, controller.pending());
+
+        controller.write_enable(0b00100);
+
+        assert_eq!(Some(Interrupt::Timer), controller.pending());
+    }
+
+    #[test]
+    fn test_pending_priority(){
+        let mut controller = InterruptController::new();
+        controller.write_enable(0xFF);
+        controller.request(Interrupt::Joypad);
+        controller.request(Interrupt::Serial);
+        controller.request(Interrupt::LcdStat);
+
+        assert_eq!(Some(Interrupt::LcdStat), controller.pending());
+
+        controller.request(Interrupt::VBlank);
+
+        assert_eq!(Some(Interrupt::VBlank), controller.pending());
+    }
+
+    #[test]
+    fn test_vectors(){
+        let vectors: Vec<u16> = Interrupt::iter().map(|interrupt| interrupt.vector()).collect();
+
+        assert_eq!(vec![0x40, 0x48, 0x50, 0x58, 0x60], vectors);
+    }
+
+    #[test]
+    fn test_registers(){
+        let mut controller = InterruptController::new();
+
+        assert_eq!(0xE0, controller.read_flag());
+        assert_eq!(0x00, controller.read_enable());
+
+        controller.write_flag(0xFF);
+        controller.write_enable(0xFF);
+
+        assert_eq!(0xFF, controller.read_flag());
+        assert_eq!(0xFF, controller.read_enable());
+
+        controller.write_flag(0x04);
+
+        assert_eq!(0xE4, controller.read_flag());
+        assert_eq!(Some(Interrupt::Timer), controller.pending());
+    }
+}
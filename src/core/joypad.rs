@@ -0,0 +1,163 @@
+use strum::EnumIter;
+
+pub(super) const JOYPAD_ADDRESS: u16 = 0xFF00;
+
+// Bits 6 and 7 of P1 are unused and always read as 1
+const JOYPAD_UNUSED_BITS: u8 = 0xC0;
+const SELECT_DIRECTIONS_BIT: u8 = 0x10;
+const SELECT_ACTIONS_BIT: u8 = 0x20;
+
+#[derive(Debug, Clone, Copy, EnumIter, PartialEq)]
+pub(crate) enum Button {
+    Right,
+    Left,
+    Up,
+    Down,
+    A,
+    B,
+    Select,
+    Start
+}
+
+impl Button {
+    // The P10-P13 line the button pulls low when its group is selected
+    fn line(self) -> u8 {
+        match self {
+            Button::Right | Button::A => 0b0001,
+            Button::Left | Button::B => 0b0010,
+            Button::Up | Button::Select => 0b0100,
+            Button::Down | Button::Start => 0b1000
+        }
+    }
+
+    fn is_direction(self) -> bool {
+        matches!(self, Button::Right | Button::Left | Button::Up | Button::Down)
+    }
+}
+
+// The P1 register (0xFF00). Selection bits and buttons are active low on hardware,
+// here they are stored active high and inverted on read.
+#[derive(Debug)]
+pub(super) struct Joypad {
+    select_directions: bool,
+    select_actions: bool,
+    directions: u8,
+    actions: u8
+}
+
+impl Joypad {
+    pub(super) fn new() -> Self {
+        Joypad {
+            select_directions: false,
+            select_actions: false,
+            directions: 0,
+            actions: 0
+        }
+    }
+
+    // Returns true if the press pulled a selected line low, which is what raises the joypad interrupt
+    pub(super) fn press(&mut self, button: Button) -> bool {
+        let lines_before = self.selected_lines();
+        if button.is_direction() {
+            self.directions |= button.line();
+        } else {
+            self.actions |= button.line();
+        }
+        self.selected_lines() & !lines_before != 0
+    }
+
+    pub(super) fn release(&mut self, button: Button) {
+        if button.is_direction() {
+            self.directions &= !button.line();
+        } else {
+            self.actions &= !button.line();
+        }
+    }
+
+    // Whether any of the selected lines is being held low, which wakes the CPU from STOP
+    pub(super) fn has_selected_input(&self) -> bool {
+        self.selected_lines() != 0
+    }
+
+    pub(super) fn read(&self) -> u8 {
+        let mut value = JOYPAD_UNUSED_BITS | (!self.selected_lines() & 0x0F);
+        if !self.select_directions {
+            value |= SELECT_DIRECTIONS_BIT;
+        }
+        if !self.select_actions {
+            value |= SELECT_ACTIONS_BIT;
+        }
+        value
+    }
+
+    pub(super) fn write(&mut self, value: u8) {
+        self.select_directions = value & SELECT_DIRECTIONS_BIT == 0;
+        self.select_actions = value & SELECT_ACTIONS_BIT == 0;
+    }
+
+    fn selected_lines(&self) -> u8 {
+        let mut lines = 0;
+        if self.select_directions {
+            lines |= self.directions;
+        }
+        if self.select_actions {
+            lines |= self.actions;
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use strum::IntoEnumIterator;
+    use crate::core::joypad::{Button, Joypad};
+
+    #[test]
+    fn test_read_nothing_selected(){
+        let mut joypad = Joypad::new();
+        joypad.press(Button::A);
+        joypad.press(Button::Down);
+
+        assert_eq!(0xFF, joypad.read());
+    }
+
+    #[test]
+    fn test_read_directions(){
+        let mut joypad = Joypad::new();
+        joypad.write(0x20);
+        joypad.press(Button::Down);
+        joypad.press(Button::A);
+
+        assert_eq!(0xE7, joypad.read());
+    }
+
+    #[test]
+    fn test_read_actions(){
+        let mut joypad = Joypad::new();
+        joypad.write(0x10);
+        joypad.press(Button::Down);
+        joypad.press(Button::B);
+
+        assert_eq!(0xDD, joypad.read());
+
+        joypad.release(Button::B);
+
+        assert_eq!(0xDF, joypad.read());
+    }
+
+    #[test]
+    fn test_press_reports_falling_edge(){
+        for button in Button::iter() {
+            let mut joypad = Joypad::new();
+
+            assert!(!joypad.press(button));
+
+            joypad.release(button);
+            joypad.write(0x00);
+
+            assert!(joypad.press(button));
+            assert!(!joypad.press(button));
+            assert!(joypad.has_selected_input());
+        }
+    }
+}
@@ -1,83 +1,635 @@
+use crate::core::boot::{BOOT_ROM_DISABLE_ADDRESS, BootRom};
+use crate::core::cartridge::base::{Cartridge, CartridgeError};
+use crate::core::cartridge::header::{CgbSupport, Header};
+use crate::core::cartridge::peripheral::Peripheral;
+use crate::core::interrupts::{Interrupt, INTERRUPT_ENABLE_ADDRESS, INTERRUPT_FLAG_ADDRESS, InterruptController};
+use crate::core::joypad::{Button, Joypad, JOYPAD_ADDRESS};
+use crate::core::model::Model;
+use crate::core::timer::{DIV_ADDRESS, TAC_ADDRESS, Timer};
+#[cfg(test)]
 use crate::util::{join_u8, split_u16};
 
+// Written by the CGB boot ROM, bit 2 makes the console run in DMG compatibility mode
+const KEY0_ADDRESS: u16 = 0xFF4C;
+const SPEED_SWITCH_ADDRESS: u16 = 0xFF4D;
+const VRAM_BANK_ADDRESS: u16 = 0xFF4F;
+const WRAM_BANK_ADDRESS: u16 = 0xFF70;
+
+const VRAM_BANK_SIZE: usize = 0x2000;
+const WRAM_BANK_SIZE: usize = 0x1000;
+// Value read from addresses nothing drives
+const OPEN_BUS: u8 = 0xFF;
+
+// I/O registers as the DMG boot ROM leaves them, for when it is skipped
+const DMG_POST_BOOT_IO: [(u16, u8); 38] = [
+    (0xFF00, 0xCF), (0xFF01, 0x00), (0xFF02, 0x7E), (0xFF05, 0x00), (0xFF06, 0x00), (0xFF07, 0xF8),
+    (0xFF0F, 0xE1), (0xFF10, 0x80), (0xFF11, 0xBF), (0xFF12, 0xF3), (0xFF13, 0xFF),
+    (0xFF14, 0xBF), (0xFF16, 0x3F), (0xFF17, 0x00), (0xFF18, 0xFF), (0xFF19, 0xBF), (0xFF1A, 0x7F),
+    (0xFF1B, 0xFF), (0xFF1C, 0x9F), (0xFF1D, 0xFF), (0xFF1E, 0xBF), (0xFF20, 0xFF), (0xFF21, 0x00),
+    (0xFF22, 0x00), (0xFF23, 0xBF), (0xFF24, 0x77), (0xFF25, 0xF3), (0xFF26, 0xF1), (0xFF40, 0x91),
+    (0xFF41, 0x85), (0xFF42, 0x00), (0xFF43, 0x00), (0xFF44, 0x00), (0xFF45, 0x00), (0xFF46, 0xFF),
+    (0xFF47, 0xFC), (0xFF4A, 0x00), (0xFF4B, 0x00)
+];
+// What the other boot ROMs leave differently, on top of the DMG values. Registers whose value
+// depends on how long the boot took, such as STAT and LY on the SGB and CGB, keep the DMG one.
+const DMG0_POST_BOOT_IO: [(u16, u8); 2] = [(0xFF41, 0x81), (0xFF44, 0x91)];
+const SGB_POST_BOOT_IO: [(u16, u8); 1] = [(0xFF26, 0xF0)];
+const CGB_POST_BOOT_IO: [(u16, u8); 8] = [
+    (0xFF02, 0x7F), (0xFF46, 0x00), (0xFF51, 0xFF), (0xFF52, 0xFF), (0xFF53, 0xFF), (0xFF54, 0xFF),
+    (0xFF55, 0xFF), (0xFF56, 0x3E)
+];
+
 #[derive(Debug)]
 pub (super) struct MemoryBus {
-    //TODO: check if this is correct, as the guide stated 0xFFFF had to be used, but that caused oob
-    memory: [u8; 0x10000]
+    // Drives 0x0000-0x7FFF and 0xA000-0xBFFF, left floating while no cartridge is inserted
+    cartridge: Option<Cartridge>,
+    pub (super) model: Model,
+    // Mapped over the cartridge at 0x0000-0x00FF until it is disabled through 0xFF50
+    boot_rom: Option<BootRom>,
+    // Two banks on the CGB, selected by VBK
+    vram: [u8; 2 * VRAM_BANK_SIZE],
+    vram_bank: usize,
+    // Bank 0 is fixed at 0xC000, the CGB can switch banks 1-7 at 0xD000 through SVBK
+    wram: [u8; 8 * WRAM_BANK_SIZE],
+    wram_bank: usize,
+    oam: [u8; 0xA0],
+    // Registers in 0xFF00-0xFF7F not owned by any component yet
+    io: [u8; 0x80],
+    hram: [u8; 0x7F],
+    pub (super) interrupts: InterruptController,
+    pub (super) joypad: Joypad,
+    pub (super) timer: Timer,
+    // Enables the CGB only registers, such as KEY1. Cleared on a CGB running a DMG game.
+    pub (super) cgb_mode: bool,
+    pub (super) double_speed: bool,
+    // Bit 0 of KEY1, makes the next STOP switch speed
+    pub (super) speed_switch_armed: bool,
+    // M-cycles the components attached to the bus have been advanced by
+    pub (super) cycles: u64
 }
 
 impl MemoryBus {
 
-    pub (super) fn new() -> Self {
+    pub (super) fn new(model: Model) -> Self {
         MemoryBus {
-            memory: [0; 0x10000]
+            model,
+            cartridge: None,
+            boot_rom: None,
+            vram: [0; 2 * VRAM_BANK_SIZE],
+            vram_bank: 0,
+            wram: [0; 8 * WRAM_BANK_SIZE],
+            wram_bank: 1,
+            oam: [0; 0xA0],
+            io: [0; 0x80],
+            hram: [0; 0x7F],
+            interrupts: InterruptController::new(),
+            joypad: Joypad::new(),
+            timer: Timer::new(),
+            cgb_mode: model.is_cgb(),
+            double_speed: false,
+            speed_switch_armed: false,
+            cycles: 0
+        }
+    }
+
+    pub (super) fn insert_cartridge(&mut self, cartridge: Cartridge) {
+        self.cartridge = Some(cartridge);
+    }
+
+    pub (super) fn map_boot_rom(&mut self, boot_rom: BootRom) {
+        self.boot_rom = Some(boot_rom);
+    }
+
+    // Leaves the I/O registers as the boot ROM would have, with the boot ROM disabled.
+    // The CGB boot ROM falls back to compatibility mode for games without CGB support.
+    pub (super) fn skip_boot_rom(&mut self) {
+        let model_io: &[(u16, u8)] = match self.model {
+            Model::Dmg0 => &DMG0_POST_BOOT_IO,
+            Model::Dmg | Model::Mgb => &[],
+            Model::Sgb | Model::Sgb2 => &SGB_POST_BOOT_IO,
+            Model::Cgb | Model::Agb => &CGB_POST_BOOT_IO
+        };
+        for (address, value) in DMG_POST_BOOT_IO.iter().chain(model_io) {
+            self.write_byte(*address, *value);
         }
+        // Writing DIV could only reset it
+        self.timer.set_counter(self.model.post_boot_div_counter());
+        self.cgb_mode = self.model.is_cgb()
+            && self.header().is_none_or(|header| header.cgb_support != CgbSupport::None);
+        self.boot_rom = None;
     }
+
+    pub (super) fn header(&self) -> Option<&Header> {
+        self.cartridge.as_ref().map(|cartridge| &cartridge.header)
+    }
+
+    pub (super) fn load_save(&mut self) -> Result<(), CartridgeError> {
+        self.cartridge.as_mut().map_or(Ok(()), |cartridge| cartridge.load_save())
+    }
+
+    pub (super) fn flush_save(&self) -> Result<(), CartridgeError> {
+        self.cartridge.as_ref().map_or(Ok(()), |cartridge| cartridge.flush_save())
+    }
+
+    pub (super) fn flush_due_save(&mut self) -> Result<(), CartridgeError> {
+        self.cartridge.as_mut().map_or(Ok(()), |cartridge| cartridge.flush_due_save())
+    }
+
+    // Advances every component attached to the bus by the given M-cycles
+    pub (super) fn tick(&mut self, m_cycles: u8) {
+        self.cycles += m_cycles as u64;
+        // The timer runs off the CPU clock, so it follows the speed switch
+        for _ in 0..m_cycles {
+            if self.timer.tick() {
+                self.request_interrupt(Interrupt::Timer);
+            }
+        }
+        if let Some(cartridge) = &mut self.cartridge {
+            // The cartridge runs off its own clock, unaffected by the CPU speed
+            let cartridge_cycles = m_cycles as u64 * if self.double_speed { 1 } else { 2 };
+            cartridge.tick(cartridge_cycles);
+        }
+    }
+    // Lets any component attached to the bus raise an interrupt, by setting its bit in IF
+    pub (super) fn request_interrupt(&mut self, interrupt: Interrupt) {
+        self.interrupts.request(interrupt);
+    }
+
+    // The input side is left to a frontend, which does not exist yet
+    #[allow(dead_code)]
+    pub (super) fn press_button(&mut self, button: Button) {
+        if self.joypad.press(button) {
+            self.request_interrupt(Interrupt::Joypad);
+        }
+    }
+
+    #[allow(dead_code)]
+    pub (super) fn release_button(&mut self, button: Button) {
+        self.joypad.release(button);
+    }
+
+    pub (super) fn connect(&mut self, peripheral: Peripheral) -> Result<(), CartridgeError> {
+        match &mut self.cartridge {
+            Some(cartridge) => cartridge.connect(peripheral),
+            None => Err(CartridgeError::MissingHardware(peripheral.name()))
+        }
+    }
+
+    // Called by STOP when KEY1 was armed
+    pub (super) fn switch_speed(&mut self) {
+        self.double_speed = !self.double_speed;
+        self.speed_switch_armed = false;
+    }
+
     pub (super) fn read_byte(&self, address: u16) -> u8 {
-        self.memory[address as usize]
+        match address {
+            0x0000..=0x7FFF => match &self.boot_rom {
+                Some(boot_rom) if boot_rom.maps(address) => boot_rom.read(address),
+                _ => self.cartridge.as_ref().map_or(OPEN_BUS, |cartridge| cartridge.read_rom(address))
+            },
+            0x8000..=0x9FFF => self.vram[self.vram_index(address)],
+            0xA000..=0xBFFF => self.cartridge.as_ref().map_or(OPEN_BUS, |cartridge| cartridge.read_ram(address)),
+            // 0xE000-0xFDFF echoes 0xC000-0xDDFF
+            0xC000..=0xFDFF => self.wram[self.wram_index(address)],
+            0xFE00..=0xFE9F => self.oam[(address - 0xFE00) as usize],
+            0xFEA0..=0xFEFF => OPEN_BUS,
+            0xFF00..=0xFF7F => self.read_io(address),
+            0xFF80..=0xFFFE => self.hram[(address - 0xFF80) as usize],
+            INTERRUPT_ENABLE_ADDRESS => self.interrupts.read_enable()
+        }
     }
 
     pub (super) fn write_byte(&mut self, address: u16, value: u8) {
-        self.memory[address as usize] = value;
+        match address {
+            0x0000..=0x7FFF => if let Some(cartridge) = &mut self.cartridge {
+                cartridge.write_rom(address, value);
+            },
+            0x8000..=0x9FFF => self.vram[self.vram_index(address)] = value,
+            0xA000..=0xBFFF => if let Some(cartridge) = &mut self.cartridge {
+                cartridge.write_ram(address, value);
+            },
+            0xC000..=0xFDFF => self.wram[self.wram_index(address)] = value,
+            0xFE00..=0xFE9F => self.oam[(address - 0xFE00) as usize] = value,
+            0xFEA0..=0xFEFF => {}
+            0xFF00..=0xFF7F => self.write_io(address, value),
+            0xFF80..=0xFFFE => self.hram[(address - 0xFF80) as usize] = value,
+            INTERRUPT_ENABLE_ADDRESS => self.interrupts.write_enable(value)
+        }
+    }
+
+    fn vram_index(&self, address: u16) -> usize {
+        self.vram_bank * VRAM_BANK_SIZE + (address - 0x8000) as usize
+    }
+
+    fn wram_index(&self, address: u16) -> usize {
+        let offset = (address as usize - 0xC000) % (2 * WRAM_BANK_SIZE);
+        if offset < WRAM_BANK_SIZE {
+            offset
+        } else {
+            self.wram_bank * WRAM_BANK_SIZE + offset - WRAM_BANK_SIZE
+        }
+    }
+
+    fn read_io(&self, address: u16) -> u8 {
+        match address {
+            JOYPAD_ADDRESS => self.joypad.read(),
+            DIV_ADDRESS..=TAC_ADDRESS => self.timer.read(address),
+            INTERRUPT_FLAG_ADDRESS => self.interrupts.read_flag(),
+            SPEED_SWITCH_ADDRESS => self.read_speed_switch(),
+            VRAM_BANK_ADDRESS if self.cgb_mode => 0xFE | self.vram_bank as u8,
+            WRAM_BANK_ADDRESS if self.cgb_mode => 0xF8 | self.wram_bank as u8,
+            BOOT_ROM_DISABLE_ADDRESS => OPEN_BUS,
+            _ if self.is_io_register(address) => self.io[(address - 0xFF00) as usize],
+            _ => OPEN_BUS
+        }
     }
 
+    fn write_io(&mut self, address: u16, value: u8) {
+        match address {
+            JOYPAD_ADDRESS => self.joypad.write(value),
+            DIV_ADDRESS..=TAC_ADDRESS => self.timer.write(address, value),
+            INTERRUPT_FLAG_ADDRESS => self.interrupts.write_flag(value),
+            // Locked once the boot ROM is unmapped
+            KEY0_ADDRESS if self.model.is_cgb() && self.boot_rom.is_some() => self.cgb_mode = value & 0x04 == 0,
+            SPEED_SWITCH_ADDRESS => self.write_speed_switch(value),
+            VRAM_BANK_ADDRESS if self.cgb_mode => self.vram_bank = (value & 0x01) as usize,
+            // Selecting bank 0 selects bank 1
+            WRAM_BANK_ADDRESS if self.cgb_mode => self.wram_bank = ((value & 0x07) as usize).max(1),
+            BOOT_ROM_DISABLE_ADDRESS if value & 0x01 != 0 => self.boot_rom = None,
+            _ if self.is_io_register(address) => self.io[(address - 0xFF00) as usize] = value,
+            _ => {}
+        }
+    }
+
+    // Whether the address belongs to a register of the serial port, APU, PPU or, on the CGB,
+    // one of its extra registers. Everything else in the I/O range is left unconnected.
+    fn is_io_register(&self, address: u16) -> bool {
+        match address {
+            0xFF01..=0xFF02 => true,
+            0xFF10..=0xFF14 | 0xFF16..=0xFF1E | 0xFF20..=0xFF26 | 0xFF30..=0xFF3F => true,
+            0xFF40..=0xFF4B => true,
+            0xFF51..=0xFF56 | 0xFF68..=0xFF6C => self.cgb_mode,
+            _ => false
+        }
+    }
+
+    fn read_speed_switch(&self) -> u8 {
+        if !self.cgb_mode {
+            return OPEN_BUS;
+        }
+        (if self.double_speed { 0x80 } else { 0x00 }) | 0x7E | self.speed_switch_armed as u8
+    }
+
+    fn write_speed_switch(&mut self, value: u8) {
+        if self.cgb_mode {
+            self.speed_switch_armed = value & 0x1 != 0;
+        }
+    }
+
+    #[cfg(test)]
     pub (super) fn read_word(&mut self, lsb_address: u16) -> u16 {
-        let lsb_value = self.memory[lsb_address as usize];
-        let msb_value = self.memory[lsb_address.wrapping_add(1) as usize];
+        let lsb_value = self.read_byte(lsb_address);
+        let msb_value = self.read_byte(lsb_address.wrapping_add(1));
         join_u8(msb_value, lsb_value)
     }
 
+    #[cfg(test)]
     pub (super) fn write_word(&mut self, lsb_address: u16, word: u16){
         let (msb_word, lsb_word) = split_u16(word);
-        self.memory[lsb_address as usize] = lsb_word;
-        self.memory[lsb_address.wrapping_add(1) as usize] = msb_word;
+        self.write_byte(lsb_address, lsb_word);
+        self.write_byte(lsb_address.wrapping_add(1), msb_word);
     }
 }
 
 #[cfg(test)]
-
 mod test{
+    use crate::core::boot::BootRom;
+    use crate::core::cartridge::base::Cartridge;
+    use crate::core::cartridge::header::{fix_checksums, test_rom};
+    use crate::core::interrupts::Interrupt;
+    use crate::core::joypad::Button;
     use crate::core::memory::MemoryBus;
+    use crate::core::model::Model;
 
     #[test]
     fn test_write_byte(){
-        let mut bus = MemoryBus::new();
+        let mut bus = MemoryBus::new(Model::Dmg);
         let value = 0x1A;
-        let address = 0xFF;
+        let address = 0xC0FF;
 
         bus.write_byte(address, value);
 
-        assert_eq!(value, bus.memory[address as usize])
+        assert_eq!(value, bus.wram[0xFF])
     }
 
     #[test]
     fn test_read_byte(){
-        let mut bus = MemoryBus::new();
+        let mut bus = MemoryBus::new(Model::Dmg);
         let value = 0x1A;
-        let address = 0xFF;
-        bus.memory[address as usize] = value;
+        let address = 0xFF90;
+        bus.hram[0x10] = value;
 
         assert_eq!(value, bus.read_byte(address))
     }
 
     #[test]
     fn test_read_word(){
-        let mut bus = MemoryBus::new();
+        let mut bus = MemoryBus::new(Model::Dmg);
         let value = 0x1234;
-        bus.memory[0xFFFF] = 0x34;
-        bus.memory[0x0] = 0x12;
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        rom[0x0000] = 0x12;
+        fix_checksums(&mut rom);
+        bus.insert_cartridge(Cartridge::new(rom).unwrap());
+        bus.write_byte(0xFFFF, 0x34);
 
         assert_eq!(value, bus.read_word(0xFFFF));
     }
 
     #[test]
     fn test_write_word(){
-        let mut bus = MemoryBus::new();
+        let mut bus = MemoryBus::new(Model::Dmg);
         let word = 0x1234;
 
-        bus.write_word(0xFFFF, word);
+        bus.write_word(0xDFFF, word);
 
-        assert_eq!(0x34, bus.memory[0xFFFF]);
-        assert_eq!(0x12, bus.memory[0x0]);
+        assert_eq!(0x34, bus.read_byte(0xDFFF));
+        assert_eq!(0x12, bus.read_byte(0xE000));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_rom_is_read_only(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+        bus.insert_cartridge(Cartridge::new(test_rom(0x00, 0x00, 0x00)).unwrap());
+
+        bus.write_byte(0x0100, 0x12);
+        bus.write_byte(0x4000, 0x34);
+        bus.write_byte(0xA000, 0x56);
+
+        assert_eq!(0x00, bus.read_byte(0x0100));
+        assert_eq!(0x00, bus.read_byte(0x4000));
+        assert_eq!(0xFF, bus.read_byte(0xA000));
+    }
+
+    #[test]
+    fn test_open_bus(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+
+        for address in [0x0000, 0x7FFF, 0xA000, 0xBFFF, 0xFEA0, 0xFEFF, 0xFF03, 0xFF4C, 0xFF7F] {
+            bus.write_byte(address, 0x00);
+
+            assert_eq!(0xFF, bus.read_byte(address), "{:#06x}", address);
+        }
+    }
+
+    #[test]
+    fn test_echo_ram(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+
+        bus.write_byte(0xC123, 0x12);
+        bus.write_byte(0xFDFF, 0x34);
+
+        assert_eq!(0x12, bus.read_byte(0xE123));
+        assert_eq!(0x34, bus.read_byte(0xDDFF));
+    }
+
+    #[test]
+    fn test_regions(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+        let regions = [(0x8000, 0x9FFF), (0xC000, 0xDFFF), (0xFE00, 0xFE9F), (0xFF80, 0xFFFE)];
+
+        for (start, end) in regions {
+            bus.write_byte(start, 0x12);
+            bus.write_byte(end, 0x34);
+
+            assert_eq!(0x12, bus.read_byte(start));
+            assert_eq!(0x34, bus.read_byte(end));
+        }
+        bus.write_byte(0xFF40, 0x91);
+
+        assert_eq!(0x91, bus.read_byte(0xFF40));
+    }
+
+    #[test]
+    fn test_vram_banks(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+        bus.write_byte(0x8000, 0x12);
+        bus.write_byte(0xFF4F, 0x01);
+
+        assert_eq!(0xFF, bus.read_byte(0xFF4F));
+        assert_eq!(0x12, bus.read_byte(0x8000));
+
+        bus.cgb_mode = true;
+        bus.write_byte(0xFF4F, 0x01);
+        bus.write_byte(0x8000, 0x34);
+
+        assert_eq!(0xFF, bus.read_byte(0xFF4F));
+        assert_eq!(0x34, bus.read_byte(0x8000));
+
+        bus.write_byte(0xFF4F, 0x00);
+
+        assert_eq!(0xFE, bus.read_byte(0xFF4F));
+        assert_eq!(0x12, bus.read_byte(0x8000));
+    }
+
+    #[test]
+    fn test_wram_banks(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+        bus.cgb_mode = true;
+        bus.write_byte(0xD000, 0x11);
+        bus.write_byte(0xFF70, 0x07);
+        bus.write_byte(0xD000, 0x77);
+
+        assert_eq!(0xFF, bus.read_byte(0xFF70));
+        assert_eq!(0x77, bus.read_byte(0xD000));
+        assert_eq!(0x77, bus.read_byte(0xF000));
+
+        bus.write_byte(0xFF70, 0x00);
+
+        assert_eq!(0xF9, bus.read_byte(0xFF70));
+        assert_eq!(0x11, bus.read_byte(0xD000));
+    }
+
+    #[test]
+    fn test_tick(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+
+        bus.tick(3);
+        bus.tick(1);
+
+        assert_eq!(4, bus.cycles);
+    }
+
+    #[test]
+    fn test_timer_interrupt(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+        bus.write_byte(0xFFFF, 0x04);
+        bus.write_byte(0xFF06, 0x80);
+        bus.write_byte(0xFF05, 0xFF);
+        bus.write_byte(0xFF07, 0x05);
+
+        bus.tick(4);
+
+        assert_eq!(0x00, bus.read_byte(0xFF05));
+        assert_eq!(None, bus.interrupts.pending());
+
+        bus.tick(1);
+
+        assert_eq!(0x80, bus.read_byte(0xFF05));
+        assert_eq!(Some(Interrupt::Timer), bus.interrupts.pending());
+    }
+
+    #[test]
+    fn test_interrupt_registers(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+
+        bus.write_byte(0xFFFF, 0x05);
+        bus.request_interrupt(Interrupt::Timer);
+
+        assert_eq!(0x05, bus.read_byte(0xFFFF));
+        assert_eq!(0xE4, bus.read_byte(0xFF0F));
+        assert_eq!(Some(Interrupt::Timer), bus.interrupts.pending());
+
+        bus.write_byte(0xFF0F, 0x00);
+
+        assert_eq!(0xE0, bus.read_byte(0xFF0F));
+        assert_eq!(None, bus.interrupts.pending());
+    }
+
+    #[test]
+    fn test_press_button_requests_interrupt(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+        bus.write_byte(0xFFFF, 0x10);
+
+        bus.press_button(Button::Start);
+
+        assert_eq!(None, bus.interrupts.pending());
+
+        bus.release_button(Button::Start);
+        bus.write_byte(0xFF00, 0x10);
+        bus.press_button(Button::Start);
+
+        assert_eq!(Some(Interrupt::Joypad), bus.interrupts.pending());
+        assert_eq!(0xD7, bus.read_byte(0xFF00));
+    }
+
+    #[test]
+    fn test_speed_switch_register(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+        bus.write_byte(0xFF4D, 0x01);
+
+        assert_eq!(0xFF, bus.read_byte(0xFF4D));
+        assert!(!bus.speed_switch_armed);
+
+        bus.cgb_mode = true;
+
+        assert_eq!(0x7E, bus.read_byte(0xFF4D));
+
+        bus.write_byte(0xFF4D, 0x01);
+
+        assert_eq!(0x7F, bus.read_byte(0xFF4D));
+
+        bus.switch_speed();
+
+        assert_eq!(0xFE, bus.read_byte(0xFF4D));
+    }
+
+    #[test]
+    fn test_boot_rom(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        rom[0x0000] = 0x12;
+        rom[0x0100] = 0x34;
+        fix_checksums(&mut rom);
+        bus.insert_cartridge(Cartridge::new(rom).unwrap());
+        bus.map_boot_rom(BootRom::new(vec![0x31; 0x100], Model::Dmg).unwrap());
+
+        assert_eq!(0x31, bus.read_byte(0x0000));
+        assert_eq!(0x31, bus.read_byte(0x00FF));
+        assert_eq!(0x34, bus.read_byte(0x0100));
+
+        bus.write_byte(0xFF50, 0x00);
+
+        assert_eq!(0x31, bus.read_byte(0x0000));
+
+        bus.write_byte(0xFF50, 0x01);
+
+        assert_eq!(0x12, bus.read_byte(0x0000));
+        assert_eq!(0xFF, bus.read_byte(0xFF50));
+    }
+
+    #[test]
+    fn test_skip_boot_rom(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+        bus.map_boot_rom(BootRom::new(vec![0x31; 0x100], Model::Dmg).unwrap());
+
+        bus.skip_boot_rom();
+
+        assert_eq!(0xFF, bus.read_byte(0x0000));
+        assert_eq!(0xCF, bus.read_byte(0xFF00));
+        assert_eq!(0xAB, bus.read_byte(0xFF04));
+        assert_eq!(0xF8, bus.read_byte(0xFF07));
+        assert_eq!(0xE1, bus.read_byte(0xFF0F));
+        assert_eq!(0x91, bus.read_byte(0xFF40));
+        assert_eq!(0xFC, bus.read_byte(0xFF47));
+        assert_eq!(0x00, bus.read_byte(0xFFFF));
+    }
+
+    #[test]
+    fn test_skip_boot_rom_per_model(){
+        let mut dmg0 = MemoryBus::new(Model::Dmg0);
+        let mut sgb = MemoryBus::new(Model::Sgb2);
+        let mut cgb = MemoryBus::new(Model::Cgb);
+
+        dmg0.skip_boot_rom();
+        sgb.skip_boot_rom();
+        cgb.skip_boot_rom();
+
+        assert_eq!(0x81, dmg0.read_byte(0xFF41));
+        assert_eq!(0x91, dmg0.read_byte(0xFF44));
+        assert_eq!(0xF1, dmg0.read_byte(0xFF26));
+        assert_eq!(0xF0, sgb.read_byte(0xFF26));
+        assert_eq!(0x85, sgb.read_byte(0xFF41));
+        assert_eq!(0x7F, cgb.read_byte(0xFF02));
+        assert_eq!(0x00, cgb.read_byte(0xFF46));
+        assert_eq!(0xFF, cgb.read_byte(0xFF55));
+        assert_eq!(0x3E, cgb.read_byte(0xFF56));
+        assert_eq!(0xE1, cgb.read_byte(0xFF0F));
+        assert_eq!(0x18, dmg0.read_byte(0xFF04));
+        assert_eq!(0x1E, cgb.read_byte(0xFF04));
+    }
+
+    #[test]
+    fn test_cgb_boot_rom(){
+        let mut bus = MemoryBus::new(Model::Cgb);
+        let mut rom = test_rom(0x00, 0x00, 0x00);
+        rom[0x0200] = 0x12;
+        fix_checksums(&mut rom);
+        bus.insert_cartridge(Cartridge::new(rom).unwrap());
+        bus.map_boot_rom(BootRom::new(vec![0x31; 0x900], Model::Cgb).unwrap());
+
+        assert_eq!(0x31, bus.read_byte(0x0000));
+        assert_eq!(b'T', bus.read_byte(0x0134));
+        assert_eq!(0x31, bus.read_byte(0x0200));
+        assert_eq!(0x31, bus.read_byte(0x08FF));
+
+        bus.write_byte(0xFF4C, 0x04);
+        bus.write_byte(0xFF50, 0x01);
+
+        assert!(!bus.cgb_mode);
+        assert_eq!(0x12, bus.read_byte(0x0200));
+
+        bus.write_byte(0xFF4C, 0x00);
+
+        assert!(!bus.cgb_mode);
+    }
+
+    #[test]
+    fn test_key0_ignored_on_dmg(){
+        let mut bus = MemoryBus::new(Model::Dmg);
+        bus.map_boot_rom(BootRom::new(vec![0x31; 0x100], Model::Dmg).unwrap());
+
+        bus.write_byte(0xFF4C, 0x00);
+
+        assert!(!bus.cgb_mode);
+    }
+}
@@ -2,3 +2,9 @@ mod registers;
 pub mod cpu;
 mod memory;
 mod instructions;
+mod interrupts;
+mod joypad;
+mod timer;
+pub mod cartridge;
+pub mod boot;
+pub mod model;
@@ -0,0 +1,80 @@
+use std::str::FromStr;
+use strum::EnumIter;
+use crate::core::boot::{BOOT_ROM_SIZE, CGB_BOOT_ROM_SIZE};
+
+// The console revisions, which differ in their boot ROM, initial state and a few quirks
+#[derive(Debug, Clone, Copy, PartialEq, EnumIter)]
+pub(crate) enum Model {
+    // The first DMG revision, with a different boot ROM
+    Dmg0,
+    Dmg,
+    // Game Boy Pocket and Light
+    Mgb,
+    Sgb,
+    Sgb2,
+    Cgb,
+    // Game Boy Advance, running in CGB mode
+    Agb
+}
+
+impl Model {
+    pub(crate) fn is_cgb(&self) -> bool {
+        matches!(self, Model::Cgb | Model::Agb)
+    }
+
+    pub(crate) fn boot_rom_size(&self) -> usize {
+        if self.is_cgb() { CGB_BOOT_ROM_SIZE } else { BOOT_ROM_SIZE }
+    }
+
+    // The internal counter behind DIV when the boot ROM hands over, which depends on how long
+    // each boot ROM runs
+    pub(crate) fn post_boot_div_counter(&self) -> u16 {
+        match self {
+            Model::Dmg0 => 0x1830,
+            Model::Dmg | Model::Mgb => 0xABCC,
+            Model::Sgb | Model::Sgb2 => 0xD85C,
+            Model::Cgb | Model::Agb => 0x1EA0
+        }
+    }
+}
+
+impl FromStr for Model {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "dmg0" => Ok(Model::Dmg0),
+            "dmg" => Ok(Model::Dmg),
+            "mgb" => Ok(Model::Mgb),
+            "sgb" => Ok(Model::Sgb),
+            "sgb2" => Ok(Model::Sgb2),
+            "cgb" => Ok(Model::Cgb),
+            "agb" => Ok(Model::Agb),
+            _ => Err(format!("unknown model {}", name))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use strum::IntoEnumIterator;
+    use crate::core::model::Model;
+
+    #[test]
+    fn test_from_str(){
+        for model in Model::iter() {
+            let name = format!("{:?}", model);
+
+            assert_eq!(Ok(model), name.parse());
+            assert_eq!(Ok(model), name.to_uppercase().parse());
+        }
+        assert!("gbc".parse::<Model>().is_err());
+    }
+
+    #[test]
+    fn test_post_boot_div_counter(){
+        assert_eq!(0x18, Model::Dmg0.post_boot_div_counter() >> 8);
+        assert_eq!(0xAB, Model::Dmg.post_boot_div_counter() >> 8);
+        assert_eq!(0x1E, Model::Cgb.post_boot_div_counter() >> 8);
+    }
+}
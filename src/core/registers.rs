@@ -1,5 +1,8 @@
+use crate::core::cartridge::header::Header;
+use crate::core::model::Model;
 use crate::util::{join_u8, split_u16};
 
+#[cfg(test)]
 pub(super) const AF_BIT_MASK: u16 = 0xFF0;
 
 const ZERO_FLAG_BYTE_POSITION: u8 = 7;
@@ -44,6 +47,50 @@ impl Registers {
         }
     }
 
+    // Values the boot ROM of the given model leaves behind. On the DMG and MGB the flags depend
+    // on the header checksum, and the CGB sets a few of them up differently for DMG games.
+    pub (super) fn post_boot(model: Model, header: Option<&Header>, cgb_mode: bool) -> Registers {
+        let mut registers = match model {
+            Model::Dmg0 => Registers::from_words(0x0100, 0xFF13, 0x00C1, 0x8403),
+            Model::Dmg | Model::Mgb => {
+                let checksum_not_zero = header.is_some_and(|header| header.header_checksum != 0);
+                let mut registers = Registers::from_words(0x0180, 0x0013, 0x00D8, 0x014D);
+                registers.f.half_carry = checksum_not_zero;
+                registers.f.carry = checksum_not_zero;
+                registers
+            }
+            Model::Sgb | Model::Sgb2 => Registers::from_words(0x0100, 0x0014, 0x0000, 0xC060),
+            Model::Cgb | Model::Agb if cgb_mode => Registers::from_words(0x1180, 0x0000, 0xFF56, 0x000D),
+            Model::Cgb | Model::Agb => {
+                let b = header.filter(|header| is_nintendo_licensee(header))
+                    .map_or(0x00, |header| header.title_checksum);
+                let hl = if b == 0x43 || b == 0x58 { 0x991A } else { 0x007C };
+                Registers::from_words(0x1180, (b as u16) << 8, 0x0008, hl)
+            }
+        };
+        // MGB and SGB2 boot ROMs only differ by the value they leave in A
+        if model == Model::Mgb || model == Model::Sgb2 {
+            registers.a = 0xFF;
+        }
+        // The AGB boot ROM runs an extra INC B before handing over
+        if model == Model::Agb {
+            registers.b = registers.b.wrapping_add(1);
+            registers.f.zero = registers.b == 0;
+            registers.f.subtract = false;
+            registers.f.half_carry = registers.b & 0x0F == 0;
+        }
+        registers
+    }
+
+    fn from_words(af: u16, bc: u16, de: u16, hl: u16) -> Registers {
+        let mut registers = Registers::new();
+        registers.set_af(af);
+        registers.set_bc(bc);
+        registers.set_de(de);
+        registers.set_hl(hl);
+        registers
+    }
+
     pub (super) fn get_af(&self) -> u16 {
         join_u8(self.a, u8::from(&self.f))
     }
@@ -79,6 +126,11 @@ impl Registers {
     }
 }
 
+// The CGB only colors DMG games it recognizes, which must have been published by Nintendo
+fn is_nintendo_licensee(header: &Header) -> bool {
+    header.old_licensee_code == 0x01 || header.new_licensee_code.as_deref() == Some("01")
+}
+
 impl From<&FlagRegister> for u8 {
     fn from(flag: &FlagRegister) -> u8 {
         (if flag.zero { 1 } else { 0 }) << ZERO_FLAG_BYTE_POSITION |
@@ -0,0 +1,238 @@
+pub(super) const DIV_ADDRESS: u16 = 0xFF04;
+pub(super) const TIMA_ADDRESS: u16 = 0xFF05;
+pub(super) const TMA_ADDRESS: u16 = 0xFF06;
+pub(super) const TAC_ADDRESS: u16 = 0xFF07;
+
+const TAC_ENABLE_BIT: u8 = 0x04;
+// Bits 3-7 of TAC are unused and always read as 1
+const TAC_UNUSED_BITS: u8 = 0xF8;
+const T_CYCLES_PER_M_CYCLE: u16 = 4;
+
+// DIV, TIMA, TMA and TAC. DIV is the upper byte of a 16 bit counter advancing every T-cycle, and
+// TIMA increments whenever the counter bit selected by TAC, ANDed with the enable bit, goes
+// from 1 to 0. That is why resetting DIV or writing TAC can increment TIMA too.
+#[derive(Debug)]
+pub(super) struct Timer {
+    counter: u16,
+    tima: u8,
+    tma: u8,
+    tac: u8,
+    // TIMA overflowed during the last M-cycle, and reads 0 until it gets reloaded on the next one.
+    // Writing TIMA in the meantime cancels the reload and the interrupt.
+    overflowed: bool,
+    // TIMA was reloaded during this M-cycle, which makes TIMA writes ignored and TMA writes
+    // go through to TIMA as well
+    reloading: bool
+}
+
+impl Timer {
+    pub(super) fn new() -> Self {
+        Timer {
+            counter: 0,
+            tima: 0,
+            tma: 0,
+            tac: 0,
+            overflowed: false,
+            reloading: false
+        }
+    }
+
+    // Starts from a given point of the counter, like after the boot ROM
+    pub(super) fn set_counter(&mut self, counter: u16) {
+        self.counter = counter;
+    }
+
+    // Advances by one M-cycle. Returns true when TIMA got reloaded, which raises the timer interrupt.
+    pub(super) fn tick(&mut self) -> bool {
+        self.reloading = self.overflowed;
+        if self.overflowed {
+            self.overflowed = false;
+            self.tima = self.tma;
+        }
+        self.set_counter_detecting_edge(self.counter.wrapping_add(T_CYCLES_PER_M_CYCLE));
+        self.reloading
+    }
+
+    // Resets the whole counter, as writing DIV or executing STOP does
+    pub(super) fn reset_div(&mut self) {
+        self.set_counter_detecting_edge(0);
+    }
+
+    pub(super) fn read(&self, address: u16) -> u8 {
+        match address {
+            DIV_ADDRESS => (self.counter >> 8) as u8,
+            TIMA_ADDRESS => self.tima,
+            TMA_ADDRESS => self.tma,
+            _ => TAC_UNUSED_BITS | self.tac
+        }
+    }
+
+    pub(super) fn write(&mut self, address: u16, value: u8) {
+        match address {
+            DIV_ADDRESS => self.reset_div(),
+            TIMA_ADDRESS => if !self.reloading {
+                self.tima = value;
+                self.overflowed = false;
+            },
+            TMA_ADDRESS => {
+                self.tma = value;
+                if self.reloading {
+                    self.tima = value;
+                }
+            }
+            _ => {
+                let signal = self.signal();
+                self.tac = value & !TAC_UNUSED_BITS;
+                if signal && !self.signal() {
+                    self.increment_tima();
+                }
+            }
+        }
+    }
+
+    fn set_counter_detecting_edge(&mut self, counter: u16) {
+        let signal = self.signal();
+        self.counter = counter;
+        if signal && !self.signal() {
+            self.increment_tima();
+        }
+    }
+
+    // The counter bit selected by TAC, gated by the enable bit
+    fn signal(&self) -> bool {
+        let bit = match self.tac & 0x03 {
+            0b00 => 9,
+            0b01 => 3,
+            0b10 => 5,
+            _ => 7
+        };
+        self.tac & TAC_ENABLE_BIT != 0 && self.counter & (1 << bit) != 0
+    }
+
+    fn increment_tima(&mut self) {
+        let (tima, overflow) = self.tima.overflowing_add(1);
+        self.tima = tima;
+        self.overflowed |= overflow;
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use crate::core::timer::Timer;
+
+    fn tick(timer: &mut Timer, m_cycles: usize) -> usize {
+        (0..m_cycles).filter(|_| timer.tick()).count()
+    }
+
+    #[test]
+    fn test_div(){
+        let mut timer = Timer::new();
+
+        tick(&mut timer, 64);
+
+        assert_eq!(0x01, timer.read(0xFF04));
+
+        timer.write(0xFF04, 0x12);
+
+        assert_eq!(0x00, timer.read(0xFF04));
+        assert_eq!(0xF8, timer.read(0xFF07));
+    }
+
+    #[test]
+    fn test_tima_frequencies(){
+        for (tac, m_cycles) in [(0x04, 256), (0x05, 4), (0x06, 16), (0x07, 64)] {
+            let mut timer = Timer::new();
+            timer.write(0xFF07, tac);
+
+            tick(&mut timer, m_cycles - 1);
+
+            assert_eq!(0x00, timer.read(0xFF05), "{:#04x}", tac);
+
+            tick(&mut timer, 1);
+
+            assert_eq!(0x01, timer.read(0xFF05), "{:#04x}", tac);
+        }
+    }
+
+    #[test]
+    fn test_div_reset_falling_edge(){
+        let mut timer = Timer::new();
+        timer.write(0xFF07, 0x05);
+        tick(&mut timer, 2);
+
+        timer.write(0xFF04, 0x00);
+
+        assert_eq!(0x01, timer.read(0xFF05));
+
+        tick(&mut timer, 1);
+        timer.write(0xFF04, 0x00);
+
+        assert_eq!(0x01, timer.read(0xFF05));
+    }
+
+    #[test]
+    fn test_tac_write_falling_edge(){
+        let mut timer = Timer::new();
+        timer.write(0xFF07, 0x05);
+        tick(&mut timer, 2);
+
+        timer.write(0xFF07, 0x01);
+
+        assert_eq!(0x01, timer.read(0xFF05));
+
+        timer.write(0xFF07, 0x05);
+        timer.write(0xFF07, 0x04);
+
+        assert_eq!(0x02, timer.read(0xFF05));
+    }
+
+    #[test]
+    fn test_delayed_reload(){
+        let mut timer = Timer::new();
+        timer.write(0xFF06, 0x42);
+        timer.write(0xFF05, 0xFF);
+        timer.write(0xFF07, 0x05);
+
+        assert_eq!(0, tick(&mut timer, 4));
+        assert_eq!(0x00, timer.read(0xFF05));
+
+        assert_eq!(1, tick(&mut timer, 1));
+        assert_eq!(0x42, timer.read(0xFF05));
+    }
+
+    #[test]
+    fn test_tima_write_cancels_reload(){
+        let mut timer = Timer::new();
+        timer.write(0xFF06, 0x42);
+        timer.write(0xFF05, 0xFF);
+        timer.write(0xFF07, 0x05);
+        tick(&mut timer, 4);
+
+        timer.write(0xFF05, 0x12);
+
+        assert_eq!(0, tick(&mut timer, 1));
+        assert_eq!(0x12, timer.read(0xFF05));
+    }
+
+    #[test]
+    fn test_writes_during_reload(){
+        let mut timer = Timer::new();
+        timer.write(0xFF06, 0x42);
+        timer.write(0xFF05, 0xFF);
+        timer.write(0xFF07, 0x05);
+        tick(&mut timer, 5);
+
+        timer.write(0xFF05, 0x12);
+
+        assert_eq!(0x42, timer.read(0xFF05));
+
+        timer.write(0xFF06, 0x34);
+
+        assert_eq!(0x34, timer.read(0xFF05));
+
+        tick(&mut timer, 1);
+        timer.write(0xFF06, 0x56);
+
+        assert_eq!(0x34, timer.read(0xFF05));
+    }
+}
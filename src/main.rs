@@ -1,9 +1,73 @@
+use std::{env, process};
+use crate::core::boot::BootRom;
+use crate::core::cartridge::base::Cartridge;
+use crate::core::cartridge::peripheral::Peripheral;
+use crate::core::cartridge::rtc::HostTimeSource;
 use crate::core::cpu::base::CPU;
+use crate::core::model::Model;
 
 mod core;
 mod util;
 
 fn main() {
-    let cpu: CPU;
-    println!("Hello, world!");
+    let mut args = env::args().skip(1).peekable();
+    let mut model = Model::Dmg;
+    if args.peek().is_some_and(|arg| arg == "--model") {
+        args.next();
+        model = match args.next().map(|name| name.parse()) {
+            Some(Ok(model)) => model,
+            Some(Err(error)) => {
+                eprintln!("{}, expected one of dmg0, dmg, mgb, sgb, sgb2, cgb or agb", error);
+                process::exit(1);
+            }
+            None => usage()
+        };
+    }
+    let Some(path) = args.next() else {
+        usage();
+    };
+    let cartridge = match Cartridge::from_file(&path) {
+        Ok(cartridge) => cartridge,
+        Err(error) => {
+            eprintln!("Could not load {}: {}", path, error);
+            process::exit(1);
+        }
+    };
+    println!("Loaded {}", cartridge.header.title);
+    if !cartridge.global_checksum_matches() {
+        eprintln!("Warning: the global checksum does not match, the ROM may be patched or corrupted");
+    }
+    let mut cpu = match args.next() {
+        Some(boot_rom_path) => match BootRom::from_file(&boot_rom_path, model) {
+            Ok(boot_rom) => CPU::with_boot_rom(model, cartridge, boot_rom),
+            Err(error) => {
+                eprintln!("Could not load {}: {}", boot_rom_path, error);
+                process::exit(1);
+            }
+        },
+        None => CPU::with_cartridge(model, cartridge)
+    };
+    // Only cartridges with a clock take it, which has to happen before loading the save
+    let _ = cpu.connect(Peripheral::Clock(Box::new(HostTimeSource::new())));
+    if let Err(error) = cpu.load_save() {
+        eprintln!("Could not load the save: {}", error);
+    }
+    // Nothing is displayed yet, so it only runs until the CPU locks up
+    let error = loop {
+        if let Err(error) = cpu.step() {
+            break error;
+        }
+        if let Err(error) = cpu.flush_due_save() {
+            eprintln!("Could not write the save: {}", error);
+        }
+    };
+    eprintln!("Stopped: {}", error);
+    if let Err(error) = cpu.flush_save() {
+        eprintln!("Could not write the save: {}", error);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: RustyBoy [--model <model>] <rom file> [boot rom file]");
+    process::exit(1);
 }
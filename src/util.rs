@@ -1,22 +1,33 @@
+#[cfg(test)]
 use std::time::{SystemTime, UNIX_EPOCH};
 
 // pseudo-random number
+#[cfg(test)]
 pub trait Randomizable {
     fn random() -> Self;
 }
 
+#[cfg(test)]
 impl Randomizable for u8 {
     fn random() -> u8 {
         (rand_from_system_time() % (u8::MAX as u32) + 1) as u8
     }
 }
 
+#[cfg(test)]
 impl Randomizable for u16 {
     fn random() -> u16 {
         (rand_from_system_time() % (u16::MAX as u32) + 1) as u16
     }
 }
 
+// pseudo-random address in work RAM, leaving room for a word
+#[cfg(test)]
+pub fn random_wram_address() -> u16 {
+    0xC000 + u16::random() % 0x1FFE
+}
+
+#[cfg(test)]
 fn rand_from_system_time() -> u32 {
     SystemTime::now().duration_since(UNIX_EPOCH).unwrap().subsec_nanos()
 }